@@ -0,0 +1,153 @@
+use crate::audio_capture::HostInfo;
+use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::{Device, HostId};
+
+/// The single cpal-backed device record every platform module enumerates into. Carrying the
+/// live `cpal::Device` handle alongside the display metadata means `select_input_device` never
+/// has to re-enumerate to turn a chosen id back into something it can open a stream on.
+#[derive(Clone)]
+pub struct EnumeratedInputDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub is_loopback: bool,
+    pub host: String,
+    pub device: Device,
+}
+
+/// Walks `host_ids` in order, listing each host's default input device first (so it's never
+/// shadowed by a duplicate name from full enumeration) and, when `enumerate_all` is set, every
+/// other input device the host reports. This is the one cpal device-listing loop every platform
+/// module drives instead of each maintaining its own; macOS's ScreenCaptureKit loopback path is
+/// additive to this, not a replacement for it, since it has no cpal `Device` to list.
+pub fn enumerate_hosts(
+    host_ids: Vec<HostId>,
+    enumerate_all: bool,
+) -> (Vec<EnumeratedInputDevice>, Vec<String>) {
+    let mut result = Vec::new();
+    let mut warnings = Vec::new();
+
+    for host_id in host_ids {
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(e) => {
+                warnings.push(format!("host {:?} unavailable: {}", host_id, e));
+                continue;
+            }
+        };
+
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+        let mut seen_names: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+        if let Some(default_device) = host.default_input_device() {
+            let raw_name = default_device
+                .name()
+                .unwrap_or_else(|_| "Unknown input device".to_string());
+            seen_names.insert(raw_name.clone());
+
+            result.push(EnumeratedInputDevice {
+                id: build_input_device_id(host_id, 0, &raw_name),
+                name: format!("{} [{}]", raw_name, host_label(host_id)),
+                is_default: true,
+                is_loopback: is_loopback_source(&raw_name),
+                host: host_label(host_id),
+                device: default_device,
+            });
+        }
+
+        if !enumerate_all {
+            continue;
+        }
+
+        let devices = match host.input_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                warnings.push(format!("host {:?} input enumeration failed: {}", host_id, e));
+                continue;
+            }
+        };
+
+        for (index, device) in devices.enumerate() {
+            let raw_name = device
+                .name()
+                .unwrap_or_else(|_| "Unknown input device".to_string());
+
+            if seen_names.contains(&raw_name) {
+                continue;
+            }
+            seen_names.insert(raw_name.clone());
+
+            let is_default = default_name
+                .as_ref()
+                .map(|default| default == &raw_name)
+                .unwrap_or(false);
+
+            result.push(EnumeratedInputDevice {
+                id: build_input_device_id(host_id, index + 1, &raw_name),
+                name: format!("{} [{}]", raw_name, host_label(host_id)),
+                is_default,
+                is_loopback: is_loopback_source(&raw_name),
+                host: host_label(host_id),
+                device,
+            });
+        }
+    }
+
+    (result, warnings)
+}
+
+/// Lists every cpal host backend on this machine (CoreAudio, WASAPI, ALSA/PulseAudio/JACK, ...)
+/// with how many input devices it reports, so callers can pin capture to a specific backend
+/// instead of relying on platform-specific env-var heuristics.
+pub fn list_hosts() -> Vec<HostInfo> {
+    let default_host_id = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .map(|host_id| {
+            let device_count = cpal::host_from_id(host_id)
+                .and_then(|host| host.input_devices())
+                .map(|devices| devices.count())
+                .unwrap_or(0);
+            HostInfo {
+                id: host_label(host_id).to_lowercase(),
+                label: host_label(host_id),
+                device_count,
+                is_default: host_id == default_host_id,
+            }
+        })
+        .collect()
+}
+
+pub fn parse_host_id(host_id: &str) -> Option<HostId> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|id| host_label(*id).eq_ignore_ascii_case(host_id))
+}
+
+pub fn host_label(host_id: HostId) -> String {
+    format!("{:?}", host_id)
+}
+
+fn build_input_device_id(host_id: HostId, index: usize, name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else {
+            slug.push('_');
+        }
+    }
+    while slug.contains("__") {
+        slug = slug.replace("__", "_");
+    }
+    let slug = slug.trim_matches('_').to_string();
+    format!("input_{}_{}_{}", host_label(host_id).to_lowercase(), index, slug)
+}
+
+pub fn is_loopback_source(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.contains("monitor")
+        || lower.contains("loopback")
+        || lower.contains("stereo mix")
+        || lower.contains("what u hear")
+}
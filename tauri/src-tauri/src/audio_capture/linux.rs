@@ -1,44 +1,646 @@
-use crate::audio_capture::{AudioCaptureState, AudioInputDevice, AudioInputSignalProbe};
+use crate::audio_capture::cpal_backend::{self, EnumeratedInputDevice};
+use crate::audio_capture::resample::StreamResampler;
+use crate::audio_capture::{
+    AggregateSourceLevel, AggregateSources, AudioCaptureState, AudioChunk, AudioInputDevice,
+    AudioInputSignalProbe, CaptureError, CaptureFormat, HostInfo, LevelMeter, PreprocessConfig,
+    VadConfig,
+};
 use base64::{engine::general_purpose, Engine as _};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, HostId, SampleFormat, StreamConfig};
 use hound::{WavSpec, WavWriter};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::HeapRb;
 use std::io::Cursor;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::thread;
+use std::time::Duration;
 
-#[derive(Clone)]
-struct EnumeratedInputDevice {
-    id: String,
-    name: String,
-    is_default: bool,
-    is_loopback: bool,
-    host: String,
-    device: Device,
-}
+/// How much audio the capture callback and the drain task are allowed to get out of sync
+/// before the callback starts dropping samples instead of blocking the audio thread.
+const RING_BUFFER_SECONDS: usize = 2;
+const DRAIN_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the single-device worker polls for a vanished device or a stream fault while
+/// idling between audio callbacks.
+const DEVICE_WATCH_INTERVAL: Duration = Duration::from_millis(200);
 
 #[derive(Default)]
 struct ProbeStats {
     sum_squares: f64,
     peak: f32,
     sample_count: u64,
+    /// Normalized samples (interleaved, same granularity the running `sum_squares`/`peak`
+    /// already treat each channel at), kept so the probe can run A-weighting and an FFT over
+    /// them once the stream stops. A probe is capped at 5s so this never grows large.
+    samples: Vec<f32>,
+}
+
+fn hann_window(x: f64, half_width: f64) -> f64 {
+    let t = (x / half_width).clamp(-1.0, 1.0);
+    0.5 + 0.5 * (std::f64::consts::PI * t).cos()
+}
+
+/// Digital IIR realization of the IEC 61672 A-weighting curve, derived by bilinear-transforming
+/// the standard analog prototype (a quadruple zero at the origin; real poles at 20.6 Hz (x2),
+/// 107.7 Hz, 737.9 Hz, and 12194.2 Hz (x2)) at the probe's actual sample rate, then normalized
+/// to 0 dB at 1 kHz. This is an approximation good enough for "is this signal or hum" probing,
+/// not calibrated SPL metrology.
+struct AWeightingFilter {
+    b: [f64; 7],
+    a: [f64; 7],
+    x_hist: [f64; 6],
+    y_hist: [f64; 6],
+}
+
+impl AWeightingFilter {
+    fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate.max(1) as f64;
+        let two_fs = 2.0 * fs;
+        let pole_freqs_hz = [20.598997_f64, 20.598997, 107.65265, 737.86223, 12194.217, 12194.217];
+
+        // Numerator from bilinear-transforming the quadruple zero at s=0 (which contributes
+        // (1-z^-1)^4) together with the (1+z^-1) each pole's transform contributes (x6 poles,
+        // i.e. (1+z^-1)^6, of which 4 cancel against the zero's degree leaving (1+z^-1)^2).
+        let mut b = poly_mul(&poly_pow(&[1.0, -1.0], 4), &poly_pow(&[1.0, 1.0], 2));
+        let mut gain = two_fs.powi(4);
+
+        let mut a = vec![1.0_f64];
+        for pole_hz in &pole_freqs_hz {
+            let pole = -2.0 * std::f64::consts::PI * pole_hz;
+            gain /= two_fs - pole;
+            let z0 = (two_fs + pole) / (two_fs - pole);
+            a = poly_mul(&a, &[1.0, -z0]);
+        }
+        for coeff in b.iter_mut() {
+            *coeff *= gain;
+        }
+
+        let gain_at_1k = freq_response_magnitude(&b, &a, 1000.0, fs);
+        if gain_at_1k > 1e-12 {
+            for coeff in b.iter_mut() {
+                *coeff /= gain_at_1k;
+            }
+        }
+
+        let mut b_arr = [0.0; 7];
+        let mut a_arr = [0.0; 7];
+        b_arr[..b.len()].copy_from_slice(&b);
+        a_arr[..a.len()].copy_from_slice(&a);
+
+        Self {
+            b: b_arr,
+            a: a_arr,
+            x_hist: [0.0; 6],
+            y_hist: [0.0; 6],
+        }
+    }
+
+    fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut out = Vec::with_capacity(samples.len());
+        for &sample in samples {
+            let x0 = sample as f64;
+            let mut y0 = self.b[0] * x0;
+            for i in 0..6 {
+                y0 += self.b[i + 1] * self.x_hist[i] - self.a[i + 1] * self.y_hist[i];
+            }
+            for i in (1..6).rev() {
+                self.x_hist[i] = self.x_hist[i - 1];
+                self.y_hist[i] = self.y_hist[i - 1];
+            }
+            self.x_hist[0] = x0;
+            self.y_hist[0] = y0;
+            out.push(y0 as f32);
+        }
+        out
+    }
+}
+
+fn poly_mul(lhs: &[f64], rhs: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; lhs.len() + rhs.len() - 1];
+    for (i, &a) in lhs.iter().enumerate() {
+        for (j, &b) in rhs.iter().enumerate() {
+            result[i + j] += a * b;
+        }
+    }
+    result
+}
+
+fn poly_pow(base: &[f64], exponent: u32) -> Vec<f64> {
+    let mut result = vec![1.0];
+    for _ in 0..exponent {
+        result = poly_mul(&result, base);
+    }
+    result
+}
+
+/// Evaluates `|B(e^{jw})/A(e^{jw})|` at `freq_hz`, used to normalize `AWeightingFilter`'s gain.
+fn freq_response_magnitude(b: &[f64], a: &[f64], freq_hz: f64, sample_rate: f64) -> f64 {
+    let omega = 2.0 * std::f64::consts::PI * freq_hz / sample_rate;
+    let eval = |coeffs: &[f64]| -> (f64, f64) {
+        coeffs.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, &c)| {
+            let phase = -(n as f64) * omega;
+            (re + c * phase.cos(), im + c * phase.sin())
+        })
+    };
+    let (num_re, num_im) = eval(b);
+    let (den_re, den_im) = eval(a);
+    let den_mag = (den_re * den_re + den_im * den_im).sqrt();
+    if den_mag < 1e-12 {
+        0.0
+    } else {
+        (num_re * num_re + num_im * num_im).sqrt() / den_mag
+    }
+}
+
+/// Smallest FFT block analyzed; below this the spectrum would be too coarse to be useful.
+const SPECTRUM_MIN_BLOCK_SIZE: usize = 256;
+/// Largest FFT block analyzed, to bound probe latency regardless of probe duration.
+const SPECTRUM_MAX_BLOCK_SIZE: usize = 4096;
+const SPECTRUM_BAND_COUNT: usize = 8;
+
+/// Returns the most recent power-of-two block of `samples` (up to `SPECTRUM_MAX_BLOCK_SIZE`,
+/// down to `SPECTRUM_MIN_BLOCK_SIZE`), Hann-windowed and FFT'd, with its magnitude spectrum
+/// bucketed into `SPECTRUM_BAND_COUNT` log-spaced bands from 50 Hz to Nyquist. `None` if there
+/// aren't even `SPECTRUM_MIN_BLOCK_SIZE` samples to analyze.
+fn compute_spectrum_bands(samples: &[f32], sample_rate: u32) -> Option<Vec<f32>> {
+    let block_size = next_pow2_floor(samples.len().min(SPECTRUM_MAX_BLOCK_SIZE));
+    if block_size < SPECTRUM_MIN_BLOCK_SIZE {
+        return None;
+    }
+    let block = &samples[samples.len() - block_size..];
+
+    let half_width = (block_size - 1) as f64 / 2.0;
+    let mut re: Vec<f64> = block
+        .iter()
+        .enumerate()
+        .map(|(i, s)| *s as f64 * hann_window(i as f64 - half_width, half_width))
+        .collect();
+    let mut im = vec![0.0_f64; block_size];
+    fft_in_place(&mut re, &mut im);
+
+    let nyquist = sample_rate as f64 / 2.0;
+    let min_freq: f64 = 50.0;
+    let max_freq = nyquist.max(min_freq * 2.0);
+    let log_min = min_freq.ln();
+    let log_span = (max_freq.ln() - log_min).max(1e-9);
+
+    let mut band_energy = vec![0.0_f64; SPECTRUM_BAND_COUNT];
+    let mut band_counts = vec![0usize; SPECTRUM_BAND_COUNT];
+    for bin in 1..block_size / 2 {
+        let freq = bin as f64 * sample_rate as f64 / block_size as f64;
+        if freq < min_freq || freq > max_freq {
+            continue;
+        }
+        let band = (((freq.ln() - log_min) / log_span) * SPECTRUM_BAND_COUNT as f64) as usize;
+        let band = band.min(SPECTRUM_BAND_COUNT - 1);
+        band_energy[band] += re[bin] * re[bin] + im[bin] * im[bin];
+        band_counts[band] += 1;
+    }
+
+    Some(
+        band_energy
+            .into_iter()
+            .zip(band_counts)
+            .map(|(sum, count)| if count == 0 { 0.0 } else { (sum / count as f64).sqrt() as f32 })
+            .collect(),
+    )
+}
+
+fn next_pow2_floor(n: usize) -> usize {
+    if n == 0 {
+        0
+    } else {
+        1usize << (usize::BITS - 1 - n.leading_zeros())
+    }
+}
+
+/// In-place iterative radix-2 Cooley-Tukey FFT; `re`/`im` must have a power-of-two length.
+fn fft_in_place(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    if n <= 1 {
+        return;
+    }
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        let (wr, wi) = (angle.cos(), angle.sin());
+        let mut i = 0;
+        while i < n {
+            let (mut cur_wr, mut cur_wi) = (1.0, 0.0);
+            for k in 0..len / 2 {
+                let u_re = re[i + k];
+                let u_im = im[i + k];
+                let v_re = re[i + k + len / 2] * cur_wr - im[i + k + len / 2] * cur_wi;
+                let v_im = re[i + k + len / 2] * cur_wi + im[i + k + len / 2] * cur_wr;
+
+                re[i + k] = u_re + v_re;
+                im[i + k] = u_im + v_im;
+                re[i + k + len / 2] = u_re - v_re;
+                im[i + k + len / 2] = u_im - v_im;
+
+                let next_wr = cur_wr * wr - cur_wi * wi;
+                let next_wi = cur_wr * wi + cur_wi * wr;
+                cur_wr = next_wr;
+                cur_wi = next_wi;
+            }
+            i += len;
+        }
+        len <<= 1;
+    }
+}
+
+/// First-order high-pass coefficient (`y[n] = a*(y[n-1] + x[n] - x[n-1])`) used to kill DC
+/// offset and sub-audible rumble ahead of the noise gate/AGC stages.
+const HIGH_PASS_COEFFICIENT: f32 = 0.98;
+
+/// Optional per-block cleanup run after downmix/resampling: high-pass, then an adaptive
+/// noise gate, then AGC. Each stage carries state across callback blocks so ballistics are
+/// continuous rather than resetting every block.
+struct Preprocessor {
+    config: PreprocessConfig,
+    /// One `(prev_input, prev_output)` high-pass state per channel, indexed by `i % channels`
+    /// over the interleaved buffer, so the filter never mixes one channel's history into
+    /// another's samples.
+    hp_state: Vec<(f32, f32)>,
+    noise_floor: f32,
+    agc_envelope: f32,
+}
+
+impl Preprocessor {
+    fn new(config: PreprocessConfig, channels: u16) -> Self {
+        Self {
+            config,
+            hp_state: vec![(0.0, 0.0); channels.max(1) as usize],
+            // Seed just above silence so the gate doesn't clamp the very first (likely
+            // near-silent) block to zero before it has anything to adapt from.
+            noise_floor: 0.0005,
+            agc_envelope: 0.0,
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        if self.config.high_pass_enabled {
+            self.apply_high_pass(samples);
+        }
+        if self.config.noise_gate_enabled {
+            self.apply_noise_gate(samples);
+        }
+        if self.config.agc_enabled {
+            self.apply_agc(samples);
+        }
+    }
+
+    fn apply_high_pass(&mut self, samples: &mut [f32]) {
+        let channels = self.hp_state.len();
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let (prev_input, prev_output) = self.hp_state[i % channels];
+            let input = *sample;
+            let output = HIGH_PASS_COEFFICIENT * (prev_output + input - prev_input);
+            self.hp_state[i % channels] = (input, output);
+            *sample = output;
+        }
+    }
+
+    fn apply_noise_gate(&mut self, samples: &mut [f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        const FLOOR_ADAPT_RATE: f32 = 0.05;
+        const GATE_MARGIN: f32 = 1.5; // ~3.5 dB above the estimated floor
+
+        let block_rms = raw_rms(samples);
+        // Only let quiet blocks pull the floor estimate down/up, so a loud speech block
+        // doesn't get mistaken for a louder "room".
+        if block_rms < self.noise_floor * GATE_MARGIN {
+            self.noise_floor += (block_rms - self.noise_floor) * FLOOR_ADAPT_RATE;
+        }
+
+        let threshold = self.noise_floor * GATE_MARGIN;
+        if threshold > 0.0 && block_rms <= threshold {
+            let attenuation = (block_rms / threshold).clamp(0.0, 1.0);
+            for sample in samples.iter_mut() {
+                *sample *= attenuation;
+            }
+        }
+    }
+
+    fn apply_agc(&mut self, samples: &mut [f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        const ATTACK_RATE: f32 = 0.3;
+        const RELEASE_RATE: f32 = 0.05;
+        const MIN_GAIN: f32 = 0.2;
+        const MAX_GAIN: f32 = 4.0;
+
+        let block_peak = peak_abs(samples);
+        let rate = if block_peak > self.agc_envelope {
+            ATTACK_RATE
+        } else {
+            RELEASE_RATE
+        };
+        self.agc_envelope += (block_peak - self.agc_envelope) * rate;
+
+        let target_rms = self.config.agc_target_rms.max(0.01);
+        let gain = (target_rms / self.agc_envelope.max(1e-4)).clamp(MIN_GAIN, MAX_GAIN);
+        for sample in samples.iter_mut() {
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// How many milliseconds of audio each VAD frame covers when estimating energy.
+const VAD_FRAME_MS: u32 = 25;
+
+/// Bundles what the drain task needs to run VAD independently of the capture path (single
+/// device vs. aggregate): the negotiated sample rate/channels are only known once the worker
+/// thread opens a device, so they're read from the shared arcs once the ring is ready.
+struct VadContext {
+    config: VadConfig,
+    sample_rate: Arc<Mutex<u32>>,
+    channels: Arc<Mutex<u16>>,
+    speech_bounds: Arc<Mutex<Option<(usize, usize)>>>,
+    stop_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<()>>>>,
+}
+
+/// Frame-energy VAD with hysteresis: an adaptive noise floor (updated only on frames
+/// classified as silence) anchors the enter/exit thresholds, and consecutive-frame counts
+/// debounce onset/offset so a single noisy frame can't flip the state.
+struct VadDetector {
+    config: VadConfig,
+    frame_len: usize,
+    leftover: Vec<f32>,
+    noise_floor_db: f32,
+    in_speech: bool,
+    consecutive_speech: u32,
+    consecutive_silence: u32,
+    silence_run_ms: u32,
+    samples_seen: usize,
+    first_speech_sample: Option<usize>,
+    last_speech_sample: Option<usize>,
+}
+
+impl VadDetector {
+    const ONSET_FRAMES: u32 = 2;
+    const OFFSET_FRAMES: u32 = 3;
+    const FLOOR_ADAPT_RATE: f32 = 0.05;
+
+    fn new(config: VadConfig, sample_rate: u32, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let frame_len =
+            ((sample_rate as usize * VAD_FRAME_MS as usize / 1000) * channels).max(channels);
+        Self {
+            config,
+            frame_len,
+            leftover: Vec::new(),
+            noise_floor_db: -60.0,
+            in_speech: false,
+            consecutive_speech: 0,
+            consecutive_silence: 0,
+            silence_run_ms: 0,
+            samples_seen: 0,
+            first_speech_sample: None,
+            last_speech_sample: None,
+        }
+    }
+
+    /// Feeds a newly drained chunk to the detector. Returns `true` once speech has been seen
+    /// and the silence run since has reached `hangover_ms`, signalling the caller to stop.
+    fn push(&mut self, chunk: &[f32]) -> bool {
+        let chunk_start = self.samples_seen;
+        self.samples_seen += chunk.len();
+
+        self.leftover.extend_from_slice(chunk);
+        let leftover_start = chunk_start - (self.leftover.len() - chunk.len());
+
+        let mut offset = 0;
+        let mut should_stop = false;
+        while offset + self.frame_len <= self.leftover.len() {
+            let frame_start = leftover_start + offset;
+            should_stop |= self.process_frame(offset, frame_start);
+            offset += self.frame_len;
+        }
+        self.leftover.drain(0..offset);
+        should_stop
+    }
+
+    fn process_frame(&mut self, offset: usize, frame_start: usize) -> bool {
+        let frame = &self.leftover[offset..offset + self.frame_len];
+        let energy_db = 20.0 * raw_rms(frame).max(1e-6).log10();
+        let enter_threshold = self.noise_floor_db + self.config.enter_db;
+        let exit_threshold = self.noise_floor_db + self.config.exit_db;
+
+        let frame_is_loud = if self.in_speech {
+            energy_db > exit_threshold
+        } else {
+            energy_db > enter_threshold
+        };
+
+        if frame_is_loud {
+            self.consecutive_speech += 1;
+            self.consecutive_silence = 0;
+            self.silence_run_ms = 0;
+        } else {
+            self.consecutive_silence += 1;
+            self.consecutive_speech = 0;
+            self.silence_run_ms += VAD_FRAME_MS;
+            self.noise_floor_db += (energy_db - self.noise_floor_db) * Self::FLOOR_ADAPT_RATE;
+        }
+
+        if !self.in_speech && self.consecutive_speech >= Self::ONSET_FRAMES {
+            self.in_speech = true;
+            self.first_speech_sample.get_or_insert(frame_start);
+        }
+        if self.in_speech {
+            self.last_speech_sample = Some(frame_start + self.frame_len);
+        }
+        if self.in_speech && self.consecutive_silence >= Self::OFFSET_FRAMES {
+            self.in_speech = false;
+        }
+
+        self.first_speech_sample.is_some() && self.silence_run_ms >= self.config.hangover_ms
+    }
+
+    fn bounds(&self) -> Option<(usize, usize)> {
+        match (self.first_speech_sample, self.last_speech_sample) {
+            (Some(first), Some(last)) => Some((first, last)),
+            _ => None,
+        }
+    }
+}
+
+/// Builds and plays an input stream for `device`, wiring its callback through the
+/// resample/preprocess pipeline into `producer`. Used both for the initial stream and to
+/// rebuild one transparently when the worker's watch loop notices the device is gone or
+/// `err_fn` fired; each rebuild gets its own fresh ring buffer (see `start_capture`'s
+/// reconnect loop) rather than sharing one producer across stream instances, so the audio
+/// callback here never has to lock anything.
+fn build_device_stream(
+    device: &Device,
+    supported_config: &cpal::SupportedStreamConfig,
+    target_sample_rate: u32,
+    format: CaptureFormat,
+    preprocess: PreprocessConfig,
+    mut producer: ringbuf::HeapProd<f32>,
+    dropped_samples: Arc<AtomicU64>,
+    level_meter: Arc<LevelMeter>,
+    stream_fault: Arc<AtomicBool>,
+    device_name: String,
+) -> Result<cpal::Stream, String> {
+    let sample_rate = supported_config.sample_rate().0;
+    let channels = supported_config.channels();
+    let config = StreamConfig {
+        channels,
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+
+    let target_channels = if format.mono { 1 } else { channels };
+    let mut resampler = StreamResampler::new(channels, sample_rate, target_channels, target_sample_rate);
+    let mut preprocessor = Preprocessor::new(preprocess, target_channels);
+    let mut ballistics = MeterBallistics::new();
+
+    let err_fault = stream_fault;
+    let err_device_name = device_name.clone();
+    let err_fn = move |err| {
+        eprintln!("Audio input stream error on '{}': {}", err_device_name, err);
+        err_fault.store(true, Ordering::Relaxed);
+    };
+
+    let stream_result = match supported_config.sample_format() {
+        SampleFormat::F32 => {
+            let level_meter = level_meter.clone();
+            let dropped_samples = dropped_samples.clone();
+            device.build_input_stream(
+                &config,
+                move |data: &[f32], _| {
+                    let mut resampled = resampler.process(data);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                    ballistics.push(
+                        &level_meter,
+                        peak_abs(data),
+                        normalized_rms_f32(data),
+                        data.len() / channels.max(1) as usize,
+                        sample_rate,
+                    );
+                },
+                err_fn,
+                None,
+            )
+        }
+        SampleFormat::I16 => {
+            let level_meter = level_meter.clone();
+            let dropped_samples = dropped_samples.clone();
+            device.build_input_stream(
+                &config,
+                move |data: &[i16], _| {
+                    let converted: Vec<f32> =
+                        data.iter().map(|s| *s as f32 / i16::MAX as f32).collect();
+                    let mut resampled = resampler.process(&converted);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                    ballistics.push(
+                        &level_meter,
+                        peak_abs_i16(data),
+                        normalized_rms_i16(data),
+                        data.len() / channels.max(1) as usize,
+                        sample_rate,
+                    );
+                },
+                err_fn,
+                None,
+            )
+        }
+        SampleFormat::U16 => {
+            let level_meter = level_meter.clone();
+            let dropped_samples = dropped_samples.clone();
+            device.build_input_stream(
+                &config,
+                move |data: &[u16], _| {
+                    let converted: Vec<f32> = data
+                        .iter()
+                        .map(|s| (*s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                        .collect();
+                    let mut resampled = resampler.process(&converted);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                    ballistics.push(
+                        &level_meter,
+                        peak_abs_u16(data),
+                        normalized_rms_u16(data),
+                        data.len() / channels.max(1) as usize,
+                        sample_rate,
+                    );
+                },
+                err_fn,
+                None,
+            )
+        }
+        other => {
+            return Err(format!(
+                "Unsupported Linux input sample format on '{}': {:?}",
+                device_name, other
+            ));
+        }
+    };
+
+    let stream = stream_result
+        .map_err(|e| format!("Failed to build Linux input stream for '{}': {}", device_name, e))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start Linux input stream for '{}': {}", device_name, e))?;
+    Ok(stream)
 }
 
 pub async fn start_capture(
     state: &AudioCaptureState,
     max_duration_secs: u32,
     selected_device_id: Option<String>,
-) -> Result<(), String> {
+    host_id: Option<String>,
+    aggregate: Option<AggregateSources>,
+    format: CaptureFormat,
+    preprocess: PreprocessConfig,
+    vad: VadConfig,
+) -> Result<tokio::sync::mpsc::Receiver<AudioChunk>, String> {
     state.reset();
 
     let samples = state.samples.clone();
-    let recent_levels = state.recent_levels.clone();
+    let level_meter = state.level_meter.clone();
     let sample_rate_arc = state.sample_rate.clone();
     let channels_arc = state.channels.clone();
     let stop_tx = state.stop_tx.clone();
     let error_arc = state.error.clone();
+    let dropped_samples = state.dropped_samples.clone();
+    let aggregate_levels = state.aggregate_levels.clone();
+    let vad_ctx = if vad.enabled {
+        Some(VadContext {
+            config: vad,
+            sample_rate: state.sample_rate.clone(),
+            channels: state.channels.clone(),
+            speech_bounds: state.speech_bounds.clone(),
+            stop_tx: state.stop_tx.clone(),
+        })
+    } else {
+        None
+    };
 
     let stop_flag = Arc::new(AtomicBool::new(false));
     let stop_flag_clone = stop_flag.clone();
@@ -51,12 +653,41 @@ pub async fn start_capture(
         stop_flag_clone.store(true, Ordering::Relaxed);
     });
 
+    // Carries ring buffer consumers from the worker thread (which knows the negotiated sample
+    // rate/channels only once the device is opened) to the async drain task: one for the
+    // initial stream, and another each time the watch loop below rebuilds the stream on
+    // reconnect. Each stream instance gets its own fresh ring buffer and owns its producer
+    // outright, so the audio callback never has to lock anything.
+    let (ring_tx, mut ring_rx) = tokio::sync::mpsc::channel::<ringbuf::HeapCons<f32>>(4);
+    let (chunk_tx, chunk_rx) = tokio::sync::mpsc::channel::<AudioChunk>(32);
+    let drain_dropped_samples = dropped_samples.clone();
+    let drain_stop_flag = stop_flag.clone();
+    let state_reconnect_count = state.reconnect_count.clone();
+    let state_last_reconnect_reason = state.last_reconnect_reason.clone();
+    let state_last_device_name = state.last_device_name.clone();
+
     thread::spawn(move || {
-        let (device, device_name, used_loopback) =
-            match select_input_device(selected_device_id.as_deref()) {
+        if let Some(aggregate) = aggregate {
+            run_aggregate_worker(
+                aggregate,
+                format,
+                preprocess,
+                sample_rate_arc,
+                channels_arc,
+                error_arc,
+                aggregate_levels,
+                dropped_samples,
+                ring_tx,
+                stop_flag,
+            );
+            return;
+        }
+
+        let (device, mut device_name, used_loopback) =
+            match select_input_device(selected_device_id.as_deref(), host_id.as_deref()) {
             Ok(result) => result,
             Err(e) => {
-                *error_arc.lock().unwrap() = Some(e);
+                *error_arc.lock().unwrap() = Some(CaptureError::DeviceUnavailable(e));
                 return;
             }
             };
@@ -66,119 +697,166 @@ pub async fn start_capture(
             "Linux audio capture: using {} source '{}'",
             source_type, device_name
         );
+        *state_last_device_name.lock().unwrap() = Some(device_name.clone());
 
         let supported_config = match device.default_input_config() {
             Ok(config) => config,
             Err(e) => {
-                *error_arc.lock().unwrap() = Some(format!(
+                *error_arc.lock().unwrap() = Some(CaptureError::BackendInit(format!(
                     "Failed to get default input config for '{}': {}",
                     device_name, e
-                ));
+                )));
                 return;
             }
         };
 
         let sample_rate = supported_config.sample_rate().0;
         let channels = supported_config.channels();
-        *sample_rate_arc.lock().unwrap() = sample_rate;
-        *channels_arc.lock().unwrap() = channels;
-
-        let config = StreamConfig {
-            channels,
-            sample_rate: cpal::SampleRate(sample_rate),
-            buffer_size: cpal::BufferSize::Default,
-        };
-
-        let stream_error_arc = error_arc.clone();
-        let stream_error_device_name = device_name.clone();
-        let err_fn = move |err| {
-            let msg = format!(
-                "Audio input stream error on '{}': {}",
-                stream_error_device_name, err
-            );
-            eprintln!("{}", msg);
-            *stream_error_arc.lock().unwrap() = Some(msg);
-        };
 
-        let stream_result = match supported_config.sample_format() {
-            SampleFormat::F32 => {
-                let samples = samples.clone();
-                let recent_levels = recent_levels.clone();
-                device.build_input_stream(
-                    &config,
-                    move |data: &[f32], _| {
-                        let mut guard = samples.lock().unwrap();
-                        guard.extend_from_slice(data);
-                        push_recent_level(&recent_levels, normalized_rms_f32(data));
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            SampleFormat::I16 => {
-                let samples = samples.clone();
-                let recent_levels = recent_levels.clone();
-                device.build_input_stream(
-                    &config,
-                    move |data: &[i16], _| {
-                        let mut guard = samples.lock().unwrap();
-                        guard.extend(data.iter().map(|s| *s as f32 / i16::MAX as f32));
-                        push_recent_level(&recent_levels, normalized_rms_i16(data));
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            SampleFormat::U16 => {
-                let samples = samples.clone();
-                let recent_levels = recent_levels.clone();
-                device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _| {
-                        let mut guard = samples.lock().unwrap();
-                        guard.extend(
-                            data.iter()
-                                .map(|s| (*s as f32 / u16::MAX as f32) * 2.0 - 1.0),
-                        );
-                        push_recent_level(&recent_levels, normalized_rms_u16(data));
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            other => {
-                *error_arc.lock().unwrap() = Some(format!(
-                    "Unsupported Linux input sample format on '{}': {:?}",
-                    device_name, other
-                ));
-                return;
-            }
+        let target_sample_rate = if format.target_sample_rate == 0 {
+            sample_rate
+        } else {
+            format.target_sample_rate
         };
+        let target_channels = if format.mono { 1 } else { channels };
+        *sample_rate_arc.lock().unwrap() = target_sample_rate;
+        *channels_arc.lock().unwrap() = target_channels;
+
+        let ring_capacity =
+            target_sample_rate as usize * target_channels as usize * RING_BUFFER_SECONDS;
+        let ring = HeapRb::<f32>::new(ring_capacity);
+        let (producer, consumer) = ring.split();
+        if ring_tx.blocking_send(consumer).is_err() {
+            *error_arc.lock().unwrap() = Some(CaptureError::StreamInterrupted(
+                "Audio drain task disappeared before capture started".to_string(),
+            ));
+            return;
+        }
 
-        let stream = match stream_result {
+        let stream_fault = Arc::new(AtomicBool::new(false));
+        let mut active_stream = Some(match build_device_stream(
+            &device,
+            &supported_config,
+            target_sample_rate,
+            format,
+            preprocess,
+            producer,
+            dropped_samples.clone(),
+            level_meter.clone(),
+            stream_fault.clone(),
+            device_name.clone(),
+        ) {
             Ok(stream) => stream,
             Err(e) => {
-                *error_arc.lock().unwrap() = Some(format!(
-                    "Failed to build Linux input stream for '{}': {}",
-                    device_name, e
-                ));
+                *error_arc.lock().unwrap() = Some(CaptureError::BackendInit(e));
                 return;
             }
-        };
+        });
 
-        if let Err(e) = stream.play() {
-            *error_arc.lock().unwrap() = Some(format!(
-                "Failed to start Linux input stream for '{}': {}",
-                device_name, e
-            ));
-            return;
-        }
+        let mut outage_started: Option<std::time::Instant> = None;
 
         while !stop_flag.load(Ordering::Relaxed) {
-            thread::sleep(std::time::Duration::from_millis(50));
+            thread::sleep(DEVICE_WATCH_INTERVAL);
+
+            let faulted = stream_fault.swap(false, Ordering::Relaxed);
+            let device_still_present = enumerate_input_devices()
+                .map(|devices| devices.iter().any(|d| d.name == device_name))
+                .unwrap_or(true);
+
+            if !faulted && device_still_present && outage_started.is_none() {
+                continue;
+            }
+
+            let reason = if !device_still_present {
+                format!("Input device '{}' disappeared", device_name)
+            } else {
+                format!("Input stream for '{}' reported an error", device_name)
+            };
+            eprintln!("Linux audio capture: {}, attempting reconnect", reason);
+            outage_started.get_or_insert_with(std::time::Instant::now);
+            drop(active_stream.take());
+
+            // Computed from the outage's start (not consumed yet) so a failed attempt doesn't
+            // lose track of how long the outage has actually run for the next retry.
+            let pending_gap_ms = outage_started.map(|started| started.elapsed().as_millis() as usize);
+
+            let reconnect_result = select_input_device(selected_device_id.as_deref(), host_id.as_deref())
+                .and_then(|(new_device, new_device_name, _)| {
+                    let new_supported_config = new_device.default_input_config().map_err(|e| {
+                        format!(
+                            "Failed to get default input config for '{}': {}",
+                            new_device_name, e
+                        )
+                    })?;
+                    // Every reconnect gets its own fresh ring buffer and hands the new
+                    // producer straight to the new stream, rather than sharing one producer
+                    // (behind a lock) across stream instances.
+                    let ring = HeapRb::<f32>::new(ring_capacity);
+                    let (mut new_producer, new_consumer) = ring.split();
+                    if let Some(gap_ms) = pending_gap_ms {
+                        let gap_frames = target_sample_rate as usize * gap_ms / 1000;
+                        let gap_samples = vec![0.0f32; gap_frames * target_channels as usize];
+                        push_ring_samples(&mut new_producer, &gap_samples, &dropped_samples);
+                    }
+                    let new_stream = build_device_stream(
+                        &new_device,
+                        &new_supported_config,
+                        target_sample_rate,
+                        format,
+                        preprocess,
+                        new_producer,
+                        dropped_samples.clone(),
+                        level_meter.clone(),
+                        stream_fault.clone(),
+                        new_device_name.clone(),
+                    )?;
+                    Ok((new_stream, new_consumer, new_device_name))
+                });
+
+            match reconnect_result {
+                Ok((new_stream, new_consumer, new_device_name)) => {
+                    if ring_tx.blocking_send(new_consumer).is_err() {
+                        eprintln!("Linux audio capture: drain task disappeared during reconnect");
+                        drop(new_stream);
+                        break;
+                    }
+                    outage_started = None;
+                    eprintln!(
+                        "Linux audio capture: reconnected, now using '{}'",
+                        new_device_name
+                    );
+                    state_reconnect_count.fetch_add(1, Ordering::Relaxed);
+                    *state_last_reconnect_reason.lock().unwrap() =
+                        Some(format!("{} -> reconnected to '{}'", reason, new_device_name));
+                    device_name = new_device_name;
+                    *state_last_device_name.lock().unwrap() = Some(device_name.clone());
+                    active_stream = Some(new_stream);
+                }
+                Err(e) => {
+                    eprintln!("Linux audio capture: reconnect attempt failed: {}", e);
+                }
+            }
         }
 
-        drop(stream);
+        drop(active_stream);
+    });
+
+    let drain_samples = samples;
+    tokio::spawn(async move {
+        let consumer = match ring_rx.recv().await {
+            Some(consumer) => consumer,
+            None => return,
+        };
+        drain_ring_buffer(
+            consumer,
+            ring_rx,
+            &drain_samples,
+            &chunk_tx,
+            &drain_dropped_samples,
+            &drain_stop_flag,
+            vad_ctx,
+        )
+        .await;
     });
 
     let stop_tx_clone = state.stop_tx.clone();
@@ -190,7 +868,253 @@ pub async fn start_capture(
         }
     });
 
-    Ok(())
+    Ok(chunk_rx)
+}
+
+struct AggregateLeg {
+    consumer: ringbuf::HeapCons<f32>,
+    label: String,
+    dropped_samples: Arc<AtomicU64>,
+}
+
+/// Opens one leg of an aggregate capture: negotiates the device's own format, downmixes and
+/// resamples it to mono at `target_sample_rate`, and feeds it into its own ring buffer so the
+/// mixer loop can pop a matching tick's worth of samples from each leg independently.
+fn open_aggregate_leg(
+    device: &EnumeratedInputDevice,
+    target_sample_rate: u32,
+    ring_capacity: usize,
+    preprocess: PreprocessConfig,
+) -> Result<(AggregateLeg, cpal::Stream), String> {
+    let supported_config = device.device.default_input_config().map_err(|e| {
+        format!(
+            "Failed to get default input config for '{}': {}",
+            device.name, e
+        )
+    })?;
+    let sample_rate = supported_config.sample_rate().0;
+    let channels = supported_config.channels();
+    let config = StreamConfig {
+        channels,
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+
+    let ring = HeapRb::<f32>::new(ring_capacity);
+    let (mut producer, consumer) = ring.split();
+    let mut resampler = StreamResampler::new(channels, sample_rate, 1, target_sample_rate);
+    let mut preprocessor = Preprocessor::new(preprocess, 1);
+    let dropped_samples = Arc::new(AtomicU64::new(0));
+
+    let err_device_name = device.name.clone();
+    let err_fn = move |err| {
+        eprintln!("Aggregate leg '{}' stream error: {}", err_device_name, err);
+    };
+
+    let stream_result = match supported_config.sample_format() {
+        SampleFormat::F32 => {
+            let dropped_samples = dropped_samples.clone();
+            device.device.build_input_stream(
+                &config,
+                move |data: &[f32], _| {
+                    let mut resampled = resampler.process(data);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                },
+                err_fn,
+                None,
+            )
+        }
+        SampleFormat::I16 => {
+            let dropped_samples = dropped_samples.clone();
+            device.device.build_input_stream(
+                &config,
+                move |data: &[i16], _| {
+                    let converted: Vec<f32> =
+                        data.iter().map(|s| *s as f32 / i16::MAX as f32).collect();
+                    let mut resampled = resampler.process(&converted);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                },
+                err_fn,
+                None,
+            )
+        }
+        SampleFormat::U16 => {
+            let dropped_samples = dropped_samples.clone();
+            device.device.build_input_stream(
+                &config,
+                move |data: &[u16], _| {
+                    let converted: Vec<f32> = data
+                        .iter()
+                        .map(|s| (*s as f32 / u16::MAX as f32) * 2.0 - 1.0)
+                        .collect();
+                    let mut resampled = resampler.process(&converted);
+                    preprocessor.process(&mut resampled);
+                    push_ring_samples(&mut producer, &resampled, &dropped_samples);
+                },
+                err_fn,
+                None,
+            )
+        }
+        other => {
+            return Err(format!(
+                "Unsupported aggregate sample format on '{}': {:?}",
+                device.name, other
+            ));
+        }
+    };
+
+    let stream = stream_result
+        .map_err(|e| format!("Failed to build aggregate stream for '{}': {}", device.name, e))?;
+
+    Ok((
+        AggregateLeg {
+            consumer,
+            label: device.name.clone(),
+            dropped_samples,
+        },
+        stream,
+    ))
+}
+
+/// Opens the primary input and the loopback/monitor source concurrently and mixes them into
+/// the unified ring buffer the rest of the capture path already knows how to drain.
+fn run_aggregate_worker(
+    aggregate: AggregateSources,
+    format: CaptureFormat,
+    preprocess: PreprocessConfig,
+    sample_rate_arc: Arc<Mutex<u32>>,
+    channels_arc: Arc<Mutex<u16>>,
+    error_arc: Arc<Mutex<Option<CaptureError>>>,
+    aggregate_levels: Arc<Mutex<Vec<AggregateSourceLevel>>>,
+    dropped_samples: Arc<AtomicU64>,
+    ring_tx: tokio::sync::mpsc::Sender<ringbuf::HeapCons<f32>>,
+    stop_flag: Arc<AtomicBool>,
+) {
+    let devices = match enumerate_input_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            *error_arc.lock().unwrap() = Some(CaptureError::DeviceUnavailable(e));
+            return;
+        }
+    };
+
+    let primary = match devices.iter().find(|d| d.id == aggregate.primary_id) {
+        Some(d) => d.clone(),
+        None => {
+            *error_arc.lock().unwrap() = Some(CaptureError::DeviceUnavailable(format!(
+                "Aggregate primary source '{}' is not available.",
+                aggregate.primary_id
+            )));
+            return;
+        }
+    };
+    let loopback = match devices.iter().find(|d| d.id == aggregate.loopback_id) {
+        Some(d) => d.clone(),
+        None => {
+            *error_arc.lock().unwrap() = Some(CaptureError::DeviceUnavailable(format!(
+                "Aggregate loopback source '{}' is not available.",
+                aggregate.loopback_id
+            )));
+            return;
+        }
+    };
+
+    let target_sample_rate = if format.target_sample_rate == 0 {
+        44100
+    } else {
+        format.target_sample_rate
+    };
+    *sample_rate_arc.lock().unwrap() = target_sample_rate;
+    *channels_arc.lock().unwrap() = 1;
+
+    let leg_ring_capacity = target_sample_rate as usize * RING_BUFFER_SECONDS;
+
+    let (primary_leg, primary_stream) =
+        match open_aggregate_leg(&primary, target_sample_rate, leg_ring_capacity, preprocess) {
+            Ok(leg) => leg,
+            Err(e) => {
+                *error_arc.lock().unwrap() = Some(CaptureError::BackendInit(format!(
+                    "Aggregate primary source failed: {}",
+                    e
+                )));
+                return;
+            }
+        };
+    let (loopback_leg, loopback_stream) =
+        match open_aggregate_leg(&loopback, target_sample_rate, leg_ring_capacity, preprocess) {
+            Ok(leg) => leg,
+            Err(e) => {
+                *error_arc.lock().unwrap() = Some(CaptureError::BackendInit(format!(
+                    "Aggregate loopback source failed: {}",
+                    e
+                )));
+                return;
+            }
+        };
+
+    if let Err(e) = primary_stream.play() {
+        *error_arc.lock().unwrap() = Some(CaptureError::StreamInterrupted(format!(
+            "Failed to start aggregate primary stream: {}",
+            e
+        )));
+        return;
+    }
+    if let Err(e) = loopback_stream.play() {
+        *error_arc.lock().unwrap() = Some(CaptureError::StreamInterrupted(format!(
+            "Failed to start aggregate loopback stream: {}",
+            e
+        )));
+        return;
+    }
+
+    let unified_ring = HeapRb::<f32>::new(leg_ring_capacity);
+    let (mut unified_producer, unified_consumer) = unified_ring.split();
+    if ring_tx.blocking_send(unified_consumer).is_err() {
+        return;
+    }
+
+    let tick_frames = (target_sample_rate / 50).max(1) as usize;
+    let mut primary_consumer = primary_leg.consumer;
+    let mut loopback_consumer = loopback_leg.consumer;
+    let mut primary_scratch = vec![0.0f32; tick_frames];
+    let mut loopback_scratch = vec![0.0f32; tick_frames];
+    let mut mixed = vec![0.0f32; tick_frames];
+
+    while !stop_flag.load(Ordering::Relaxed) {
+        primary_scratch.iter_mut().for_each(|s| *s = 0.0);
+        loopback_scratch.iter_mut().for_each(|s| *s = 0.0);
+        primary_consumer.pop_slice(&mut primary_scratch);
+        loopback_consumer.pop_slice(&mut loopback_scratch);
+
+        for i in 0..tick_frames {
+            let mixed_sample = primary_scratch[i] * aggregate.primary_gain
+                + loopback_scratch[i] * aggregate.loopback_gain;
+            mixed[i] = mixed_sample.clamp(-1.0, 1.0);
+        }
+        push_ring_samples(&mut unified_producer, &mixed, &dropped_samples);
+
+        *aggregate_levels.lock().unwrap() = vec![
+            AggregateSourceLevel {
+                label: primary_leg.label.clone(),
+                peak: peak_abs(&primary_scratch),
+                rms: raw_rms(&primary_scratch),
+                dropped_samples: primary_leg.dropped_samples.load(Ordering::Relaxed),
+            },
+            AggregateSourceLevel {
+                label: loopback_leg.label.clone(),
+                peak: peak_abs(&loopback_scratch),
+                rms: raw_rms(&loopback_scratch),
+                dropped_samples: loopback_leg.dropped_samples.load(Ordering::Relaxed),
+            },
+        ];
+
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    drop(primary_stream);
+    drop(loopback_stream);
 }
 
 pub async fn stop_capture(state: &AudioCaptureState) -> Result<String, String> {
@@ -201,13 +1125,21 @@ pub async fn stop_capture(state: &AudioCaptureState) -> Result<String, String> {
     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
 
     if let Some(error) = state.error.lock().unwrap().as_ref() {
-        return Err(error.clone());
+        return Err(error.to_string());
     }
 
-    let samples = state.samples.lock().unwrap().clone();
+    let mut samples = state.samples.lock().unwrap().clone();
     let sample_rate = *state.sample_rate.lock().unwrap();
     let channels = *state.channels.lock().unwrap();
 
+    if let Some((first, last)) = *state.speech_bounds.lock().unwrap() {
+        let first = first.min(samples.len());
+        let last = last.min(samples.len());
+        if last > first {
+            samples = samples[first..last].to_vec();
+        }
+    }
+
     if samples.is_empty() {
         return Err(
             "No audio samples captured. On WSL2, verify host microphone access is enabled for WSL/WSLg."
@@ -215,6 +1147,10 @@ pub async fn stop_capture(state: &AudioCaptureState) -> Result<String, String> {
         );
     }
 
+    // `sample_rate`/`channels` already reflect the `CaptureFormat` the caller negotiated at
+    // `start_capture` time (the live resampler in `build_device_stream`/`open_aggregate_leg`
+    // already converted every sample to it), so the WAV we write out matches whatever format
+    // was streamed during capture instead of silently overriding it.
     let peak = peak_abs(&samples);
     let rms = normalized_rms_f32(&samples);
     if peak < 0.01 && rms < 0.015 {
@@ -222,6 +1158,13 @@ pub async fn stop_capture(state: &AudioCaptureState) -> Result<String, String> {
     }
 
     let wav_data = samples_to_wav(&samples, sample_rate, channels)?;
+    let dropped = state.dropped_samples.load(Ordering::Relaxed);
+    if dropped > 0 {
+        eprintln!(
+            "Linux audio capture: {} samples were dropped due to ring buffer overrun",
+            dropped
+        );
+    }
     let base64_data = general_purpose::STANDARD.encode(&wav_data);
     Ok(base64_data)
 }
@@ -232,8 +1175,17 @@ pub fn is_supported() -> bool {
         .unwrap_or(false)
 }
 
-pub fn list_input_devices() -> Result<Vec<AudioInputDevice>, String> {
-    let devices = enumerate_input_devices()?;
+/// Enumerates the cpal host backends available on this machine (ALSA, PulseAudio, JACK, ...)
+/// so callers can pin capture to a specific one instead of relying on env-var heuristics.
+pub fn list_hosts() -> Vec<HostInfo> {
+    cpal_backend::list_hosts()
+}
+
+pub fn list_input_devices(host_id: Option<String>) -> Result<Vec<AudioInputDevice>, String> {
+    let devices = match host_id {
+        Some(host_id) => enumerate_input_devices_for_host(&host_id)?,
+        None => enumerate_input_devices()?,
+    };
     Ok(devices
         .into_iter()
         .map(|device| AudioInputDevice {
@@ -254,9 +1206,11 @@ pub fn list_input_devices() -> Result<Vec<AudioInputDevice>, String> {
 
 pub fn probe_input_signal(
     selected_device_id: Option<String>,
+    host_id: Option<String>,
     duration_ms: u64,
 ) -> Result<AudioInputSignalProbe, String> {
-    let (device, device_name, _used_loopback) = select_input_device(selected_device_id.as_deref())?;
+    let (device, device_name, _used_loopback) =
+        select_input_device(selected_device_id.as_deref(), host_id.as_deref())?;
     let supported_config = device.default_input_config().map_err(|e| {
         format!(
             "Failed to get default input config for '{}': {}",
@@ -349,12 +1303,22 @@ pub fn probe_input_signal(
             has_signal: false,
             message: "No samples captured during probe. Source may be inactive or blocked."
                 .to_string(),
+            a_weighted_rms: 0.0,
+            spectrum_bands: None,
         });
     }
 
     let rms = (stats.sum_squares / stats.sample_count as f64).sqrt() as f32;
     let normalized_level = (rms * 3.0).clamp(0.0, 1.0);
-    let has_signal = stats.peak >= 0.01 || rms >= 0.005;
+
+    let probe_sample_rate = config.sample_rate.0;
+    let a_weighted_samples = AWeightingFilter::new(probe_sample_rate).process(&stats.samples);
+    let a_weighted_rms = raw_rms(&a_weighted_samples);
+    let spectrum_bands = compute_spectrum_bands(&stats.samples, probe_sample_rate);
+
+    // A-weighting de-emphasizes mains hum and DC offset, so a source that only passes the flat
+    // peak/RMS thresholds because of those no longer reads as real signal.
+    let has_signal = (stats.peak >= 0.01 || rms >= 0.005) && a_weighted_rms >= 0.003;
     let message = if has_signal {
         "Signal detected. This source should work for capture.".to_string()
     } else if is_wsl_environment() {
@@ -372,10 +1336,19 @@ pub fn probe_input_signal(
         normalized_level,
         has_signal,
         message,
+        a_weighted_rms,
+        spectrum_bands,
     })
 }
 
-fn select_input_device(selected_device_id: Option<&str>) -> Result<(Device, String, bool), String> {
+fn select_input_device(
+    selected_device_id: Option<&str>,
+    host_id: Option<&str>,
+) -> Result<(Device, String, bool), String> {
+    if let Some(host_id) = host_id {
+        return select_input_device_on_host(selected_device_id, host_id);
+    }
+
     let devices = enumerate_input_devices()?;
 
     if let Some(selected_id) = selected_device_id {
@@ -406,7 +1379,7 @@ fn select_input_device(selected_device_id: Option<&str>) -> Result<(Device, Stri
         }
 
         if !should_enumerate_all_inputs() {
-            let expanded_devices = enumerate_input_devices_with_options(true)?;
+            let expanded_devices = enumerate_input_devices_with_options(true, None)?;
             if let Some(candidate) = expanded_devices.iter().find(|device| {
                 let lower = device.name.to_ascii_lowercase();
                 !device.is_loopback && (lower.contains("rdpsource") || lower.contains("pulse"))
@@ -461,85 +1434,77 @@ fn select_input_device(selected_device_id: Option<&str>) -> Result<(Device, Stri
     )
 }
 
-fn enumerate_input_devices() -> Result<Vec<EnumeratedInputDevice>, String> {
-    enumerate_input_devices_with_options(should_enumerate_all_inputs())
-}
+/// Device selection pinned to a caller-chosen host, bypassing the WSL/Pulse heuristics that
+/// only make sense when the host itself is being guessed.
+fn select_input_device_on_host(
+    selected_device_id: Option<&str>,
+    host_id: &str,
+) -> Result<(Device, String, bool), String> {
+    let devices = enumerate_input_devices_for_host(host_id)?;
 
-fn enumerate_input_devices_with_options(enumerate_all_inputs: bool) -> Result<Vec<EnumeratedInputDevice>, String> {
-    let host_ids = prioritized_host_ids();
-    let mut result = Vec::new();
-    let mut warnings: Vec<String> = Vec::new();
+    if let Some(selected_id) = selected_device_id {
+        return devices
+            .iter()
+            .find(|d| d.id == selected_id)
+            .map(|candidate| {
+                (
+                    candidate.device.clone(),
+                    candidate.name.clone(),
+                    candidate.is_loopback,
+                )
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Selected input device '{}' is not available on host '{}'. Refresh the device list and try again.",
+                    selected_id, host_id
+                )
+            });
+    }
 
-    for host_id in host_ids {
-        let host = match cpal::host_from_id(host_id) {
-            Ok(host) => host,
-            Err(e) => {
-                warnings.push(format!("host {:?} unavailable: {}", host_id, e));
-                continue;
-            }
-        };
+    devices
+        .iter()
+        .find(|d| d.is_default)
+        .or_else(|| devices.iter().find(|d| d.is_loopback))
+        .or_else(|| devices.first())
+        .map(|candidate| {
+            (
+                candidate.device.clone(),
+                candidate.name.clone(),
+                candidate.is_loopback,
+            )
+        })
+        .ok_or_else(|| format!("No input devices found on host '{}'.", host_id))
+}
 
-        let default_name = host.default_input_device().and_then(|d| d.name().ok());
-        let mut seen_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-        if let Some(default_device) = host.default_input_device() {
-            let raw_name = default_device
-                .name()
-                .unwrap_or_else(|_| "Unknown input device".to_string());
-            seen_names.insert(raw_name.clone());
-
-            let display_name = format!("{} [{}]", raw_name, format!("{:?}", host_id));
-            result.push(EnumeratedInputDevice {
-                id: build_input_device_id(host_id, 0, &raw_name),
-                name: display_name,
-                is_default: true,
-                is_loopback: is_loopback_source(&raw_name),
-                host: format!("{:?}", host_id),
-                device: default_device,
-            });
-        }
+fn enumerate_input_devices() -> Result<Vec<EnumeratedInputDevice>, String> {
+    enumerate_input_devices_with_options(should_enumerate_all_inputs(), None)
+}
 
-        if !enumerate_all_inputs {
-            continue;
-        }
+/// Enumerates devices restricted to a single host, identified by the label returned from
+/// `list_hosts`/`HostInfo::id` (case-insensitive).
+fn enumerate_input_devices_for_host(host_id: &str) -> Result<Vec<EnumeratedInputDevice>, String> {
+    let parsed = parse_host_id(host_id)
+        .ok_or_else(|| format!("Unknown audio host '{}'.", host_id))?;
+    enumerate_input_devices_with_options(true, Some(parsed))
+}
 
-        let devices = match host.input_devices() {
-            Ok(devices) => devices,
-            Err(e) => {
-                warnings.push(format!("host {:?} input enumeration failed: {}", host_id, e));
-                continue;
-            }
-        };
+fn parse_host_id(host_id: &str) -> Option<HostId> {
+    cpal_backend::parse_host_id(host_id)
+}
 
-        for (index, device) in devices.enumerate() {
-            let raw_name = device
-                .name()
-                .unwrap_or_else(|_| "Unknown input device".to_string());
+/// Thin Linux-specific wrapper around the shared `cpal_backend::enumerate_hosts` loop: it picks
+/// which hosts to walk (honoring the WSL/Pulse host-ordering heuristics) and turns the shared
+/// backend's "no devices" case into a Linux-flavored, actionable error message.
+fn enumerate_input_devices_with_options(
+    enumerate_all_inputs: bool,
+    host_filter: Option<HostId>,
+) -> Result<Vec<EnumeratedInputDevice>, String> {
+    let host_ids = match host_filter {
+        Some(host_id) => vec![host_id],
+        None => prioritized_host_ids(),
+    };
 
-            if seen_names.contains(&raw_name) {
-                continue;
-            }
-            seen_names.insert(raw_name.clone());
-
-            let id = build_input_device_id(host_id, index + 1, &raw_name);
-            let is_default = default_name
-                .as_ref()
-                .map(|default| default == &raw_name)
-                .unwrap_or(false);
-
-            // Include host label to avoid ambiguity between duplicated device names.
-            let display_name = format!("{} [{}]", raw_name, format!("{:?}", host_id));
-
-            result.push(EnumeratedInputDevice {
-                id,
-                name: display_name,
-                is_default,
-                is_loopback: is_loopback_source(&raw_name),
-                host: format!("{:?}", host_id),
-                device,
-            });
-        }
-    }
+    let (result, warnings) = cpal_backend::enumerate_hosts(host_ids, enumerate_all_inputs);
 
     if result.is_empty() {
         let pulse_server = std::env::var("PULSE_SERVER").ok();
@@ -549,9 +1514,7 @@ fn enumerate_input_devices_with_options(enumerate_all_inputs: bool) -> Result<Ve
         } else {
             format!(" Details: {}", warnings.join("; "))
         };
-        let mut message = format!(
-            "No Linux input devices found across CPAL hosts. On WSL2, ensure WSLg/PulseAudio is available and Windows microphone privacy access is enabled for desktop apps."
-        );
+        let mut message = "No Linux input devices found across CPAL hosts. On WSL2, ensure WSLg/PulseAudio is available and Windows microphone privacy access is enabled for desktop apps.".to_string();
         if pulse_plugin_missing {
             message.push_str(" ALSA Pulse plugin is missing. Install it in WSL: sudo apt-get update && sudo apt-get install -y libasound2-plugins pulseaudio-utils alsa-utils");
         }
@@ -606,7 +1569,7 @@ fn prioritized_host_ids() -> Vec<HostId> {
 }
 
 fn host_label(host_id: HostId) -> String {
-    format!("{:?}", host_id)
+    cpal_backend::host_label(host_id)
 }
 
 fn should_enumerate_all_inputs() -> bool {
@@ -624,41 +1587,184 @@ fn is_wsl_environment() -> bool {
     std::env::var("WSL_DISTRO_NAME").is_ok() || std::env::var("WSL_INTEROP").is_ok()
 }
 
-fn build_input_device_id(host_id: HostId, index: usize, name: &str) -> String {
-    let mut slug = String::with_capacity(name.len());
-    for c in name.chars() {
-        if c.is_ascii_alphanumeric() {
-            slug.push(c.to_ascii_lowercase());
-        } else {
-            slug.push('_');
+
+/// Pushes interleaved samples into the ring buffer and counts any that don't fit because the
+/// drain task has fallen behind. Called from the cpal callback, from the worker thread when it
+/// fills a reconnect gap with silence, and from the aggregate mixer loop.
+fn push_ring_samples(
+    producer: &mut ringbuf::HeapProd<f32>,
+    data: &[f32],
+    dropped_samples: &Arc<AtomicU64>,
+) {
+    let pushed = producer.push_slice(data);
+    if pushed < data.len() {
+        dropped_samples.fetch_add((data.len() - pushed) as u64, Ordering::Relaxed);
+    }
+}
+
+/// Runs on a tokio task: periodically drains the ring buffer into the full-recording
+/// accumulator and forwards each drained slice as a streaming chunk.
+async fn drain_ring_buffer(
+    mut consumer: ringbuf::HeapCons<f32>,
+    mut ring_rx: tokio::sync::mpsc::Receiver<ringbuf::HeapCons<f32>>,
+    samples: &Arc<Mutex<Vec<f32>>>,
+    chunk_tx: &tokio::sync::mpsc::Sender<AudioChunk>,
+    dropped_samples: &Arc<AtomicU64>,
+    stop_flag: &Arc<AtomicBool>,
+    vad: Option<VadContext>,
+) {
+    let mut vad_detector = vad.as_ref().map(|ctx| {
+        let sample_rate = *ctx.sample_rate.lock().unwrap();
+        let channels = *ctx.channels.lock().unwrap();
+        VadDetector::new(ctx.config, sample_rate, channels)
+    });
+
+    let mut scratch = vec![0.0f32; 4096];
+    loop {
+        // The worker thread sends a fresh consumer each time it rebuilds the stream on
+        // reconnect; flush whatever the old ring still holds (it stops receiving new audio
+        // the moment the old stream is torn down, but may hold up to one DRAIN_INTERVAL's
+        // worth that was pushed and never popped) before switching to the new one, so
+        // reconnect doesn't quietly drop a bounded tail of audio.
+        if let Ok(new_consumer) = ring_rx.try_recv() {
+            drain_remaining(
+                &mut consumer,
+                &mut scratch,
+                samples,
+                chunk_tx,
+                dropped_samples,
+                &mut vad_detector,
+                &vad,
+            )
+            .await;
+            consumer = new_consumer;
+        }
+
+        let stopping = stop_flag.load(Ordering::Relaxed);
+        let popped = consumer.pop_slice(&mut scratch);
+        if popped > 0 {
+            let chunk = &scratch[..popped];
+            samples.lock().unwrap().extend_from_slice(chunk);
+            let dropped = dropped_samples.swap(0, Ordering::Relaxed);
+            let _ = chunk_tx
+                .send(AudioChunk {
+                    pcm_base64: pcm_chunk_to_base64(chunk),
+                    dropped_samples: dropped,
+                })
+                .await;
+
+            if let (Some(detector), Some(ctx)) = (vad_detector.as_mut(), vad.as_ref()) {
+                let should_stop = detector.push(chunk);
+                *ctx.speech_bounds.lock().unwrap() = detector.bounds();
+                if should_stop {
+                    if let Some(tx) = ctx.stop_tx.lock().unwrap().take() {
+                        let _ = tx.try_send(());
+                    }
+                }
+            }
+        } else if stopping {
+            break;
+        }
+
+        if !stopping || popped > 0 {
+            tokio::time::sleep(DRAIN_INTERVAL).await;
         }
     }
-    while slug.contains("__") {
-        slug = slug.replace("__", "_");
+}
+
+/// Pops `consumer` until it's empty, forwarding each batch exactly like the main drain loop
+/// body (accumulator, streaming chunk, VAD). Used right before swapping to a reconnected
+/// stream's consumer, so whatever the old ring still held isn't silently discarded.
+async fn drain_remaining(
+    consumer: &mut ringbuf::HeapCons<f32>,
+    scratch: &mut [f32],
+    samples: &Arc<Mutex<Vec<f32>>>,
+    chunk_tx: &tokio::sync::mpsc::Sender<AudioChunk>,
+    dropped_samples: &Arc<AtomicU64>,
+    vad_detector: &mut Option<VadDetector>,
+    vad: &Option<VadContext>,
+) {
+    loop {
+        let popped = consumer.pop_slice(scratch);
+        if popped == 0 {
+            break;
+        }
+        let chunk = &scratch[..popped];
+        samples.lock().unwrap().extend_from_slice(chunk);
+        let dropped = dropped_samples.swap(0, Ordering::Relaxed);
+        let _ = chunk_tx
+            .send(AudioChunk {
+                pcm_base64: pcm_chunk_to_base64(chunk),
+                dropped_samples: dropped,
+            })
+            .await;
+
+        if let (Some(detector), Some(ctx)) = (vad_detector.as_mut(), vad.as_ref()) {
+            let should_stop = detector.push(chunk);
+            *ctx.speech_bounds.lock().unwrap() = detector.bounds();
+            if should_stop {
+                if let Some(tx) = ctx.stop_tx.lock().unwrap().take() {
+                    let _ = tx.try_send(());
+                }
+            }
+        }
+
+        if popped < scratch.len() {
+            break;
+        }
     }
-    let slug = slug.trim_matches('_').to_string();
-    let host_slug = format!("{:?}", host_id).to_lowercase();
-    format!("input_{}_{}_{}", host_slug, index, slug)
 }
 
-fn is_loopback_source(name: &str) -> bool {
-    let lower = name.to_lowercase();
-    lower.contains("monitor")
-        || lower.contains("loopback")
-        || lower.contains("stereo mix")
-        || lower.contains("what u hear")
+fn pcm_chunk_to_base64(samples: &[f32]) -> String {
+    let mut bytes = Vec::with_capacity(samples.len() * 2);
+    for sample in samples {
+        let clamped = sample.clamp(-1.0, 1.0);
+        let i16_sample = (clamped * 32767.0) as i16;
+        bytes.extend_from_slice(&i16_sample.to_le_bytes());
+    }
+    general_purpose::STANDARD.encode(bytes)
+}
+
+/// Peak-hold ballistics applied before publishing to the lock-free `LevelMeter`: peak holds
+/// instantly on attack, then decays at ~11.8 dB/s (standard VU/PPM ballistics) rather than
+/// tracking the raw instantaneous block peak. Lives on the audio callback's stack across
+/// blocks; the publish step itself is a single atomic store.
+struct MeterBallistics {
+    held_peak: f32,
 }
 
-fn push_recent_level(levels: &Arc<std::sync::Mutex<Vec<f32>>>, level: f32) {
-    let clamped = level.clamp(0.0, 1.0);
-    let mut guard = levels.lock().unwrap();
-    guard.push(clamped);
-    if guard.len() > 240 {
-        let overflow = guard.len() - 240;
-        guard.drain(0..overflow);
+impl MeterBallistics {
+    const DECAY_DB_PER_SEC: f32 = 11.8;
+
+    fn new() -> Self {
+        Self { held_peak: 0.0 }
+    }
+
+    fn push(&mut self, meter: &LevelMeter, block_peak: f32, block_rms: f32, block_frames: usize, sample_rate: u32) {
+        if block_frames == 0 {
+            return;
+        }
+        let elapsed_secs = block_frames as f32 / sample_rate.max(1) as f32;
+        let decay_db = Self::DECAY_DB_PER_SEC * elapsed_secs;
+        let decayed = self.held_peak * 10f32.powf(-decay_db / 20.0);
+        self.held_peak = block_peak.max(decayed).clamp(0.0, 1.0);
+        meter.publish(self.held_peak, block_rms.clamp(0.0, 1.0));
     }
 }
 
+fn peak_abs_i16(samples: &[i16]) -> f32 {
+    samples.iter().fold(0.0_f32, |max_value, sample| {
+        max_value.max((*sample as f32 / i16::MAX as f32).abs())
+    })
+}
+
+fn peak_abs_u16(samples: &[u16]) -> f32 {
+    samples.iter().fold(0.0_f32, |max_value, sample| {
+        let normalized = (*sample as f32 / u16::MAX as f32) * 2.0 - 1.0;
+        max_value.max(normalized.abs())
+    })
+}
+
 fn accumulate_probe_stats_f32(target: &Arc<Mutex<ProbeStats>>, data: &[f32]) {
     if data.is_empty() {
         return;
@@ -669,6 +1775,7 @@ fn accumulate_probe_stats_f32(target: &Arc<Mutex<ProbeStats>>, data: &[f32]) {
         guard.peak = guard.peak.max(value);
         guard.sum_squares += (*sample as f64) * (*sample as f64);
         guard.sample_count += 1;
+        guard.samples.push(*sample);
     }
 }
 
@@ -682,6 +1789,7 @@ fn accumulate_probe_stats_i16(target: &Arc<Mutex<ProbeStats>>, data: &[i16]) {
         guard.peak = guard.peak.max(normalized.abs());
         guard.sum_squares += (normalized as f64) * (normalized as f64);
         guard.sample_count += 1;
+        guard.samples.push(normalized);
     }
 }
 
@@ -695,6 +1803,7 @@ fn accumulate_probe_stats_u16(target: &Arc<Mutex<ProbeStats>>, data: &[u16]) {
         guard.peak = guard.peak.max(normalized.abs());
         guard.sum_squares += (normalized as f64) * (normalized as f64);
         guard.sample_count += 1;
+        guard.samples.push(normalized);
     }
 }
 
@@ -704,6 +1813,14 @@ fn peak_abs(samples: &[f32]) -> f32 {
         .fold(0.0_f32, |max_value, sample| max_value.max(sample.abs()))
 }
 
+fn raw_rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares = samples.iter().map(|sample| sample * sample).sum::<f32>();
+    (sum_squares / samples.len() as f32).sqrt()
+}
+
 fn normalized_rms_f32(samples: &[f32]) -> f32 {
     if samples.is_empty() {
         return 0.0;
@@ -771,3 +1888,78 @@ fn samples_to_wav(samples: &[f32], sample_rate: u32, channels: u16) -> Result<Ve
 
     Ok(buffer)
 }
+
+#[cfg(test)]
+mod vad_tests {
+    use super::*;
+
+    fn loud_frame(len: usize) -> Vec<f32> {
+        vec![0.5; len]
+    }
+
+    fn quiet_frame(len: usize) -> Vec<f32> {
+        vec![0.0001; len]
+    }
+
+    fn detector() -> VadDetector {
+        VadDetector::new(VadConfig::default(), 16_000, 1)
+    }
+
+    /// A single loud frame must not flip speech on; onset needs `ONSET_FRAMES` consecutive
+    /// loud frames, so one noisy frame in otherwise-silent audio can't trigger a false start.
+    #[test]
+    fn onset_requires_consecutive_loud_frames() {
+        let mut vad = detector();
+        let frame_len = vad.frame_len;
+
+        vad.push(&loud_frame(frame_len));
+        assert!(!vad.in_speech, "one loud frame should not trigger onset");
+
+        vad.push(&loud_frame(frame_len));
+        assert!(vad.in_speech, "onset frame count should trigger speech");
+        assert!(vad.bounds().is_some());
+    }
+
+    /// Once in speech, offset needs `OFFSET_FRAMES` consecutive quiet frames; fewer than that
+    /// must leave `in_speech` set so a brief dip mid-sentence doesn't cut the recording short.
+    #[test]
+    fn offset_requires_consecutive_quiet_frames() {
+        let mut vad = detector();
+        let frame_len = vad.frame_len;
+
+        vad.push(&loud_frame(frame_len));
+        vad.push(&loud_frame(frame_len));
+        assert!(vad.in_speech);
+
+        for _ in 0..(VadDetector::OFFSET_FRAMES - 1) {
+            vad.push(&quiet_frame(frame_len));
+            assert!(vad.in_speech, "should stay in speech before offset threshold");
+        }
+
+        vad.push(&quiet_frame(frame_len));
+        assert!(!vad.in_speech, "offset frame count should end speech");
+    }
+
+    /// `push` should only signal "stop capturing" once speech has actually been seen and the
+    /// silence run since then has reached the configured hangover, not on plain silence.
+    #[test]
+    fn hangover_only_fires_after_speech_was_seen() {
+        let config = VadConfig {
+            enabled: true,
+            hangover_ms: VAD_FRAME_MS as u32 * 2,
+            ..VadConfig::default()
+        };
+        let mut vad = VadDetector::new(config, 16_000, 1);
+        let frame_len = vad.frame_len;
+
+        assert!(!vad.push(&quiet_frame(frame_len)));
+        assert!(!vad.push(&quiet_frame(frame_len)));
+
+        vad.push(&loud_frame(frame_len));
+        vad.push(&loud_frame(frame_len));
+        assert!(vad.bounds().is_some());
+
+        assert!(!vad.push(&quiet_frame(frame_len)));
+        assert!(vad.push(&quiet_frame(frame_len)));
+    }
+}
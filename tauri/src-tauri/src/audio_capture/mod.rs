@@ -1,9 +1,16 @@
+/// Shared cpal-backed device enumeration, used by every platform module so device listing and
+/// format negotiation only have one implementation. Platforms add capture paths cpal can't
+/// express on top of this (e.g. macOS's ScreenCaptureKit loopback) rather than re-deriving it.
+mod cpal_backend;
 #[cfg(target_os = "macos")]
 mod macos;
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "linux")]
 mod linux;
+mod monitor;
+mod opus_stream;
+mod resample;
 
 #[cfg(target_os = "macos")]
 pub use macos::*;
@@ -11,7 +18,12 @@ pub use macos::*;
 pub use windows::*;
 #[cfg(target_os = "linux")]
 pub use linux::*;
+pub use monitor::*;
+pub use opus_stream::*;
 
+use hound::{WavSpec, WavWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 #[cfg(target_os = "macos")]
@@ -28,6 +40,81 @@ pub struct AudioInputDevice {
     pub diagnostics: Option<String>,
 }
 
+/// Why capture start or a mid-capture stream failed. Replaces a free-form `String` so the UI
+/// can tell "the OS denied microphone access" (prompt for permission) apart from "the device
+/// vanished" (offer re-selection) instead of pattern-matching an error message.
+#[derive(Debug, Clone)]
+pub enum CaptureError {
+    /// The OS denied microphone/screen-capture access. Not currently raised on Linux (cpal has
+    /// no permission layer there), but every platform module reports through this type so a
+    /// macOS/Windows permission prompt denial surfaces the same way.
+    PermissionDenied(String),
+    DeviceUnavailable(String),
+    UnsupportedFormat(String),
+    BackendInit(String),
+    StreamInterrupted(String),
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::PermissionDenied(msg) => write!(f, "permission denied: {}", msg),
+            CaptureError::DeviceUnavailable(msg) => write!(f, "device unavailable: {}", msg),
+            CaptureError::UnsupportedFormat(msg) => write!(f, "unsupported format: {}", msg),
+            CaptureError::BackendInit(msg) => write!(f, "backend initialization failed: {}", msg),
+            CaptureError::StreamInterrupted(msg) => write!(f, "stream interrupted: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl CaptureError {
+    /// The `AudioInputDevice::permission_state` value this error implies.
+    fn permission_state(&self) -> &'static str {
+        match self {
+            CaptureError::PermissionDenied(_) => "denied",
+            _ => "unknown",
+        }
+    }
+
+    fn diagnostic_code(&self) -> &'static str {
+        match self {
+            CaptureError::PermissionDenied(_) => "permission_denied",
+            CaptureError::DeviceUnavailable(_) => "device_unavailable",
+            CaptureError::UnsupportedFormat(_) => "unsupported_format",
+            CaptureError::BackendInit(_) => "backend_init",
+            CaptureError::StreamInterrupted(_) => "stream_interrupted",
+        }
+    }
+}
+
+/// Folds a `CaptureError` into an `AudioInputDevice` record so a device list re-fetched after a
+/// failed capture can explain why the previously selected device isn't usable, rather than
+/// surfacing the opaque message the old `Arc<Mutex<Option<String>>>` field held.
+pub fn annotate_device_with_error(device: &mut AudioInputDevice, error: &CaptureError) {
+    device.permission_state = error.permission_state().to_string();
+    device.diagnostics = Some(format!("{}: {}", error.diagnostic_code(), error));
+}
+
+/// `list_input_devices`, with the entry for `state`'s last-attempted device annotated with its
+/// capture error (if any) — so a device list re-fetched right after a failed capture can show
+/// "this one was denied permission" instead of every device looking equally available.
+pub fn list_input_devices_for_state(
+    host_id: Option<String>,
+    state: &AudioCaptureState,
+) -> Result<Vec<AudioInputDevice>, String> {
+    let mut devices = list_input_devices(host_id)?;
+    if let Some(error) = get_capture_error(state) {
+        if let Some(name) = state.last_device_name.lock().unwrap().as_ref() {
+            if let Some(device) = devices.iter_mut().find(|d| &d.name == name) {
+                annotate_device_with_error(device, &error);
+            }
+        }
+    }
+    Ok(devices)
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AudioInputSignalProbe {
     pub device_name: String,
@@ -38,15 +125,239 @@ pub struct AudioInputSignalProbe {
     pub normalized_level: f32,
     pub has_signal: bool,
     pub message: String,
+    /// RMS after IEC 61672 A-weighting, approximating perceived loudness and de-emphasizing
+    /// mains hum/DC offset that would otherwise read as signal on the flat `rms` above.
+    pub a_weighted_rms: f32,
+    /// Hann-windowed FFT magnitude spectrum bucketed into a handful of log-spaced bands, so
+    /// callers can tell tonal signal from broadband noise. `None` if the probe was too short to
+    /// fill even the smallest analysis block.
+    pub spectrum_bands: Option<Vec<f32>>,
+}
+
+/// A slice of interleaved audio handed to streaming consumers while capture is still in
+/// progress, produced by draining the capture ring buffer ahead of `stop_capture`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioChunk {
+    pub pcm_base64: String,
+    pub dropped_samples: u64,
+}
+
+/// Target format the capture path converts device audio into before it reaches the ring
+/// buffer, so WAV output and streaming chunks are speech-backend-ready by default.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct CaptureFormat {
+    pub target_sample_rate: u32,
+    pub mono: bool,
+}
+
+impl Default for CaptureFormat {
+    fn default() -> Self {
+        Self {
+            target_sample_rate: 16000,
+            mono: true,
+        }
+    }
+}
+
+/// A cpal host backend (ALSA, PulseAudio, JACK, ...) as a first-class, selectable entity
+/// instead of something only inferred from env vars.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HostInfo {
+    pub id: String,
+    pub label: String,
+    pub device_count: usize,
+    pub is_default: bool,
+}
+
+/// Opts capture into mixing a real input and a loopback/monitor source into one timeline,
+/// e.g. for recording "me + remote audio" in a meeting.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AggregateSources {
+    pub primary_id: String,
+    pub loopback_id: String,
+    pub primary_gain: f32,
+    pub loopback_gain: f32,
+}
+
+/// Per-source level reported while an `AggregateSources` capture is running, so the UI can
+/// tell whether one leg (e.g. the loopback monitor) is silent.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AggregateSourceLevel {
+    pub label: String,
+    pub peak: f32,
+    pub rms: f32,
+    /// Cumulative samples this leg has lost to its own ring buffer overrun since capture
+    /// started, so a leg dropping samples between mixer ticks doesn't hide behind an unaffected
+    /// peak/rms reading. Unlike `get_dropped_sample_count`, which reports only what the drain
+    /// task has not yet attached to a streamed chunk, this count is never reset mid-capture.
+    pub dropped_samples: u64,
+}
+
+/// WebRTC-style per-block cleanup applied after downmix/resampling and before samples reach
+/// the ring buffer. Every stage is opt-in; `probe_input_signal` never applies any of this, so
+/// it keeps reporting the true, unprocessed input level.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct PreprocessConfig {
+    pub high_pass_enabled: bool,
+    pub noise_gate_enabled: bool,
+    pub agc_enabled: bool,
+    pub agc_target_rms: f32,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            high_pass_enabled: false,
+            noise_gate_enabled: false,
+            agc_enabled: false,
+            agc_target_rms: 0.1,
+        }
+    }
+}
+
+/// Frame-energy voice activity detection applied to the drained sample stream. When enabled,
+/// `stop_capture` trims everything outside the first/last detected speech frame, and capture
+/// ends on its own `hangover_ms` after speech stops rather than waiting for `max_duration_secs`.
+/// `enter_db`/`exit_db` are hysteresis margins in dB above the adaptive noise floor (enter
+/// higher than exit, so a frame that trips speech doesn't immediately drop back out).
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct VadConfig {
+    pub enabled: bool,
+    pub hangover_ms: u32,
+    pub enter_db: f32,
+    pub exit_db: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hangover_ms: 800,
+            enter_db: 9.0,
+            exit_db: 6.0,
+        }
+    }
+}
+
+/// Sample encoding for `AudioCaptureState::write_wav`. PCM16 keeps files small and is what
+/// most downstream tooling expects; Float32 skips the clamp/round quantization for callers
+/// that want the exact captured values for offline inspection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum WavExportFormat {
+    Pcm16,
+    Float32,
+}
+
+fn wav_spec_for(sample_rate: u32, channels: u16, format: WavExportFormat) -> WavSpec {
+    match format {
+        WavExportFormat::Pcm16 => WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+        WavExportFormat::Float32 => WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        },
+    }
+}
+
+/// Single reading published by the lock-free level meter: a peak-hold value (instant attack,
+/// ~11.8 dB/s decay) and a windowed RMS computed over the same block.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct MeterFrame {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+/// Lock-free peak/RMS meter: the audio callback publishes a new reading with a single atomic
+/// store (peak and RMS packed into one `u64`), and `get_recent_levels` reads the latest one
+/// with a single atomic load. The producer side never allocates or locks.
+pub struct LevelMeter {
+    packed: AtomicU64,
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        let meter = Self {
+            packed: AtomicU64::new(0),
+        };
+        meter.publish(0.0, 0.0);
+        meter
+    }
+
+    pub fn publish(&self, peak: f32, rms: f32) {
+        let bits = ((peak.to_bits() as u64) << 32) | (rms.to_bits() as u64);
+        self.packed.store(bits, Ordering::Release);
+    }
+
+    pub fn read(&self) -> MeterFrame {
+        let bits = self.packed.load(Ordering::Acquire);
+        MeterFrame {
+            peak: f32::from_bits((bits >> 32) as u32),
+            rms: f32::from_bits((bits & 0xFFFF_FFFF) as u32),
+        }
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_wav_samples<W: std::io::Write + std::io::Seek>(
+    writer: &mut WavWriter<W>,
+    samples: &[f32],
+    format: WavExportFormat,
+) -> Result<(), String> {
+    match format {
+        WavExportFormat::Pcm16 => {
+            for sample in samples {
+                let clamped = sample.clamp(-1.0, 1.0);
+                let pcm_sample = (clamped * 32767.0).round() as i16;
+                writer
+                    .write_sample(pcm_sample)
+                    .map_err(|e| format!("Failed to write WAV sample: {}", e))?;
+            }
+        }
+        WavExportFormat::Float32 => {
+            for sample in samples {
+                writer
+                    .write_sample(*sample)
+                    .map_err(|e| format!("Failed to write WAV sample: {}", e))?;
+            }
+        }
+    }
+    Ok(())
 }
 
 pub struct AudioCaptureState {
     pub samples: Arc<Mutex<Vec<f32>>>,
-    pub recent_levels: Arc<Mutex<Vec<f32>>>,
+    pub level_meter: Arc<LevelMeter>,
     pub sample_rate: Arc<Mutex<u32>>,
     pub channels: Arc<Mutex<u16>>,
     pub stop_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<()>>>>,
-    pub error: Arc<Mutex<Option<String>>>,
+    pub error: Arc<Mutex<Option<CaptureError>>>,
+    pub dropped_samples: Arc<AtomicU64>,
+    pub aggregate_levels: Arc<Mutex<Vec<AggregateSourceLevel>>>,
+    /// First/last sample index (into `samples`) the VAD classified as speech, published by
+    /// the drain task as it processes each chunk. `None` until VAD is enabled and has seen
+    /// at least one speech frame.
+    pub speech_bounds: Arc<Mutex<Option<(usize, usize)>>>,
+    /// How many times the worker has transparently rebuilt the input stream mid-capture
+    /// (device unplugged, stream error) plus a human-readable description of the last one,
+    /// so the UI can show "input switched to X" instead of silently losing audio.
+    pub reconnect_count: Arc<AtomicU64>,
+    pub last_reconnect_reason: Arc<Mutex<Option<String>>>,
+    /// Name of the device the worker thread last attempted to open, so a device list re-fetched
+    /// after a failed capture can annotate the one entry that actually failed instead of guessing.
+    /// `None` until a device has been selected (e.g. aggregate capture, which has no single
+    /// device to annotate).
+    pub last_device_name: Arc<Mutex<Option<String>>>,
     #[cfg(target_os = "macos")]
     pub stream: Arc<Mutex<Option<SCStream>>>,
 }
@@ -55,11 +366,17 @@ impl AudioCaptureState {
     pub fn new() -> Self {
         Self {
             samples: Arc::new(Mutex::new(Vec::new())),
-            recent_levels: Arc::new(Mutex::new(Vec::new())),
+            level_meter: Arc::new(LevelMeter::new()),
             sample_rate: Arc::new(Mutex::new(44100)),
             channels: Arc::new(Mutex::new(2)),
             stop_tx: Arc::new(Mutex::new(None)),
             error: Arc::new(Mutex::new(None)),
+            dropped_samples: Arc::new(AtomicU64::new(0)),
+            aggregate_levels: Arc::new(Mutex::new(Vec::new())),
+            speech_bounds: Arc::new(Mutex::new(None)),
+            reconnect_count: Arc::new(AtomicU64::new(0)),
+            last_reconnect_reason: Arc::new(Mutex::new(None)),
+            last_device_name: Arc::new(Mutex::new(None)),
             #[cfg(target_os = "macos")]
             stream: Arc::new(Mutex::new(None)),
         }
@@ -67,11 +384,79 @@ impl AudioCaptureState {
 
     pub fn reset(&self) {
         *self.samples.lock().unwrap() = Vec::new();
-        *self.recent_levels.lock().unwrap() = Vec::new();
+        self.level_meter.publish(0.0, 0.0);
         *self.error.lock().unwrap() = None;
+        self.dropped_samples.store(0, Ordering::Relaxed);
+        *self.aggregate_levels.lock().unwrap() = Vec::new();
+        *self.speech_bounds.lock().unwrap() = None;
+        self.reconnect_count.store(0, Ordering::Relaxed);
+        *self.last_reconnect_reason.lock().unwrap() = None;
+        *self.last_device_name.lock().unwrap() = None;
     }
+
+    /// Serializes the full captured buffer to a RIFF/WAVE file at `path`.
+    pub fn write_wav(&self, path: &Path, format: WavExportFormat) -> Result<(), String> {
+        let samples = self.samples.lock().unwrap();
+        let sample_rate = *self.sample_rate.lock().unwrap();
+        let channels = *self.channels.lock().unwrap();
+
+        let spec = wav_spec_for(sample_rate, channels, format);
+        let mut writer = WavWriter::create(path, spec)
+            .map_err(|e| format!("Failed to create WAV file at {:?}: {}", path, e))?;
+        write_wav_samples(&mut writer, &samples, format)?;
+        writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV file at {:?}: {}", path, e))
+    }
+
+    /// Same as `write_wav`, but writes the buffer in fixed-size chunks and flushes the file
+    /// (header included) after each one, so the data written so far is on disk and readable
+    /// during a long export instead of only once the writer is finalized.
+    pub fn write_wav_streaming(&self, path: &Path, format: WavExportFormat) -> Result<(), String> {
+        const FLUSH_CHUNK_SAMPLES: usize = 65536;
+
+        let samples = self.samples.lock().unwrap();
+        let sample_rate = *self.sample_rate.lock().unwrap();
+        let channels = *self.channels.lock().unwrap();
+
+        let spec = wav_spec_for(sample_rate, channels, format);
+        let mut writer = WavWriter::create(path, spec)
+            .map_err(|e| format!("Failed to create WAV file at {:?}: {}", path, e))?;
+
+        for chunk in samples.chunks(FLUSH_CHUNK_SAMPLES) {
+            write_wav_samples(&mut writer, chunk, format)?;
+            writer
+                .flush()
+                .map_err(|e| format!("Failed to flush WAV data to {:?}: {}", path, e))?;
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV file at {:?}: {}", path, e))
+    }
+}
+
+/// Reads the most recently published meter frame. Unlike the old history-buffer version,
+/// this never blocks on the audio callback: it's a single atomic load.
+pub fn get_recent_levels(state: &AudioCaptureState) -> MeterFrame {
+    state.level_meter.read()
+}
+
+pub fn get_dropped_sample_count(state: &AudioCaptureState) -> u64 {
+    state.dropped_samples.load(Ordering::Relaxed)
+}
+
+pub fn get_aggregate_levels(state: &AudioCaptureState) -> Vec<AggregateSourceLevel> {
+    state.aggregate_levels.lock().unwrap().clone()
+}
+
+pub fn get_capture_error(state: &AudioCaptureState) -> Option<CaptureError> {
+    state.error.lock().unwrap().clone()
 }
 
-pub fn get_recent_levels(state: &AudioCaptureState) -> Vec<f32> {
-    state.recent_levels.lock().unwrap().clone()
+pub fn get_reconnect_status(state: &AudioCaptureState) -> (u64, Option<String>) {
+    (
+        state.reconnect_count.load(Ordering::Relaxed),
+        state.last_reconnect_reason.lock().unwrap().clone(),
+    )
 }
@@ -0,0 +1,199 @@
+use crate::audio_capture::AudioCaptureState;
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::HeapRb;
+use rodio::{OutputStream, Sink};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How much audio the monitor's ring buffer holds between the poll task (producer) and the
+/// rodio playback thread (consumer); bounds monitoring latency regardless of how far capture
+/// and playback ever drift apart.
+const MONITOR_BUFFER_MS: usize = 300;
+/// How often the poll task checks `AudioCaptureState::samples` for newly captured audio.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorConfig {
+    pub gain: f32,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+/// Holds the running monitor's stop handshake and a live-adjustable gain. Monitoring defaults
+/// off (no monitor is started until `start_monitor` is called) to avoid feeding captured mic
+/// audio back into the same room the mic hears.
+pub struct MonitorState {
+    stop_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<()>>>>,
+    gain_bits: Arc<AtomicU32>,
+}
+
+impl MonitorState {
+    pub fn new() -> Self {
+        Self {
+            stop_tx: Arc::new(Mutex::new(None)),
+            gain_bits: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stop_tx.lock().unwrap().is_some()
+    }
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `rodio::Source` reading from the bounded ring buffer `start_monitor`'s poll task fills,
+/// applying a live-adjustable gain per sample. Reports silence instead of blocking when the
+/// buffer runs dry, so a capture stall never stalls the output device's audio thread.
+struct MonitorSource {
+    consumer: ringbuf::HeapCons<f32>,
+    channels: u16,
+    sample_rate: u32,
+    gain_bits: Arc<AtomicU32>,
+}
+
+impl Iterator for MonitorSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let gain = f32::from_bits(self.gain_bits.load(Ordering::Relaxed));
+        Some(self.consumer.try_pop().unwrap_or(0.0) * gain)
+    }
+}
+
+impl rodio::Source for MonitorSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Starts tailing `capture_state.samples` and playing it back out the default output device in
+/// near real time, so a user can confirm they're recording the right source at the right level.
+/// Runs until `stop_monitor` is called; capture itself is unaffected.
+pub async fn start_monitor(
+    capture_state: &AudioCaptureState,
+    monitor_state: &MonitorState,
+    config: MonitorConfig,
+) -> Result<(), String> {
+    monitor_state
+        .gain_bits
+        .store(config.gain.max(0.0).to_bits(), Ordering::Relaxed);
+
+    let sample_rate = (*capture_state.sample_rate.lock().unwrap()).max(1);
+    let channels = (*capture_state.channels.lock().unwrap()).max(1);
+    let ring_capacity =
+        (sample_rate as usize * channels as usize * MONITOR_BUFFER_MS / 1000).max(channels as usize);
+    let ring = HeapRb::<f32>::new(ring_capacity);
+    let (mut producer, consumer) = ring.split();
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+    *monitor_state.stop_tx.lock().unwrap() = Some(tx);
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let poll_stop_flag = stop_flag.clone();
+    let playback_stop_flag = stop_flag.clone();
+    tokio::spawn(async move {
+        rx.recv().await;
+        stop_flag.store(true, Ordering::Relaxed);
+    });
+
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel::<Result<(), String>>();
+    let gain_bits = monitor_state.gain_bits.clone();
+    thread::spawn(move || {
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok(pair) => pair,
+            Err(e) => {
+                let _ = ready_tx.send(Err(format!("Failed to open monitor output device: {}", e)));
+                return;
+            }
+        };
+        let sink = match Sink::try_new(&handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                let _ =
+                    ready_tx.send(Err(format!("Failed to create monitor playback sink: {}", e)));
+                return;
+            }
+        };
+
+        sink.append(MonitorSource {
+            consumer,
+            channels,
+            sample_rate,
+            gain_bits,
+        });
+        let _ = ready_tx.send(Ok(()));
+
+        while !playback_stop_flag.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(50));
+        }
+        sink.stop();
+        drop(stream);
+    });
+
+    ready_rx
+        .await
+        .map_err(|_| "Monitor playback thread exited before starting".to_string())??;
+
+    let samples = capture_state.samples.clone();
+    tokio::spawn(async move {
+        let mut cursor = 0usize;
+        loop {
+            if poll_stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let new_chunk: Vec<f32> = {
+                let guard = samples.lock().unwrap();
+                if cursor >= guard.len() {
+                    drop(guard);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+                let chunk = guard[cursor..].to_vec();
+                cursor = guard.len();
+                chunk
+            };
+
+            producer.push_slice(&new_chunk);
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    Ok(())
+}
+
+pub async fn stop_monitor(monitor_state: &MonitorState) -> Result<(), String> {
+    if let Some(tx) = monitor_state.stop_tx.lock().unwrap().take() {
+        let _ = tx.send(()).await;
+    }
+    Ok(())
+}
+
+pub fn set_monitor_gain(monitor_state: &MonitorState, gain: f32) {
+    monitor_state
+        .gain_bits
+        .store(gain.max(0.0).to_bits(), Ordering::Relaxed);
+}
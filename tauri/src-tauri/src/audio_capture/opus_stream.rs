@@ -0,0 +1,164 @@
+use crate::audio_capture::resample::StreamResampler;
+use crate::audio_capture::AudioCaptureState;
+use opus::{Application, Bitrate, Channels, Encoder as OpusEncoder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Opus streaming always encodes at 48 kHz in fixed 20 ms frames (960 samples/channel), the
+/// size RFC 6716 recommends for interactive voice.
+pub const OPUS_SAMPLE_RATE: u32 = 48000;
+const OPUS_FRAME_MS: u32 = 20;
+const OPUS_FRAME_SAMPLES_PER_CHANNEL: usize =
+    (OPUS_SAMPLE_RATE as usize / 1000) * OPUS_FRAME_MS as usize;
+/// Largest an Opus frame can be per RFC 6716; sized so the encode output buffer never needs
+/// to grow.
+const MAX_OPUS_FRAME_BYTES: usize = 1275;
+/// How often the streaming task checks `AudioCaptureState::samples` for newly drained audio.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Copy)]
+pub enum OpusApplicationMode {
+    Voip,
+    Audio,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OpusStreamConfig {
+    pub channels: u16,
+    pub bitrate: i32,
+    pub application: OpusApplicationMode,
+}
+
+impl Default for OpusStreamConfig {
+    fn default() -> Self {
+        Self {
+            channels: 1,
+            bitrate: 24000,
+            application: OpusApplicationMode::Voip,
+        }
+    }
+}
+
+/// Mirrors `AudioCaptureState`'s `stop_tx` handshake so starting/stopping an Opus stream
+/// follows the same shape as starting/stopping capture itself.
+pub struct OpusStreamState {
+    pub stop_tx: Arc<Mutex<Option<tokio::sync::mpsc::Sender<()>>>>,
+}
+
+impl OpusStreamState {
+    pub fn new() -> Self {
+        Self {
+            stop_tx: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl Default for OpusStreamState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn opus_channels(channels: u16) -> Channels {
+    if channels <= 1 {
+        Channels::Mono
+    } else {
+        Channels::Stereo
+    }
+}
+
+/// Starts tailing `capture_state.samples` and emitting Opus packets over the returned
+/// channel. Capture itself is unaffected; this just taps the same accumulator `stop_capture`
+/// reads from, so it can run for the lifetime of a capture session or be stopped earlier.
+pub async fn start_opus_stream(
+    capture_state: &AudioCaptureState,
+    opus_state: &OpusStreamState,
+    config: OpusStreamConfig,
+) -> Result<tokio::sync::mpsc::Receiver<Vec<u8>>, String> {
+    let application = match config.application {
+        OpusApplicationMode::Voip => Application::Voip,
+        OpusApplicationMode::Audio => Application::Audio,
+    };
+    let mut encoder = OpusEncoder::new(OPUS_SAMPLE_RATE, opus_channels(config.channels), application)
+        .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::Bits(config.bitrate))
+        .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
+    *opus_state.stop_tx.lock().unwrap() = Some(tx);
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let stop_flag_clone = stop_flag.clone();
+    tokio::spawn(async move {
+        rx.recv().await;
+        stop_flag_clone.store(true, Ordering::Relaxed);
+    });
+
+    let (packet_tx, packet_rx) = tokio::sync::mpsc::channel::<Vec<u8>>(32);
+
+    let samples = capture_state.samples.clone();
+    let sample_rate_arc = capture_state.sample_rate.clone();
+    let channels_arc = capture_state.channels.clone();
+    // Must match opus_channels()'s clamp: the encoder below is built for mono or stereo only,
+    // so frame sizing and the resampler's output channel count have to agree with that, not
+    // with whatever channel count the caller asked for.
+    let target_channels: u16 = if config.channels <= 1 { 1 } else { 2 };
+
+    tokio::spawn(async move {
+        let mut cursor = 0usize;
+        let mut resampler: Option<StreamResampler> = None;
+        let mut pending = Vec::<f32>::new();
+        let mut output_buf = vec![0u8; MAX_OPUS_FRAME_BYTES];
+        let frame_len = OPUS_FRAME_SAMPLES_PER_CHANNEL * target_channels as usize;
+
+        loop {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let new_chunk: Vec<f32> = {
+                let guard = samples.lock().unwrap();
+                if cursor >= guard.len() {
+                    drop(guard);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+                let chunk = guard[cursor..].to_vec();
+                cursor = guard.len();
+                chunk
+            };
+
+            let src_rate = *sample_rate_arc.lock().unwrap();
+            let src_channels = *channels_arc.lock().unwrap();
+            let resampler = resampler.get_or_insert_with(|| {
+                StreamResampler::new(src_channels, src_rate, target_channels, OPUS_SAMPLE_RATE)
+            });
+            pending.extend_from_slice(&resampler.process(&new_chunk));
+
+            while pending.len() >= frame_len {
+                let frame: Vec<f32> = pending.drain(0..frame_len).collect();
+                match encoder.encode_float(&frame, &mut output_buf) {
+                    Ok(len) => {
+                        if packet_tx.send(output_buf[..len].to_vec()).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => eprintln!("Opus encode error: {}", e),
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+
+    Ok(packet_rx)
+}
+
+pub async fn stop_opus_stream(opus_state: &OpusStreamState) -> Result<(), String> {
+    if let Some(tx) = opus_state.stop_tx.lock().unwrap().take() {
+        let _ = tx.send(()).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+/// Two-point linear interpolator for a single channel; no low-pass filtering is applied, so
+/// downsampling does not remove content above the new Nyquist frequency. The read position is
+/// the exact fraction `pos_num / dst_rate` into the virtual sequence `[last_sample] ++ block`,
+/// so interpolation across a block boundary only ever needs the one carried-over sample, and
+/// the output count does not depend on how the input was split into blocks.
+struct LinearResampler {
+    src_rate: u64,
+    dst_rate: u64,
+    pos_num: u64,
+    last_sample: f32,
+}
+
+impl LinearResampler {
+    fn new(src_rate: u32, dst_rate: u32) -> Self {
+        Self {
+            src_rate: src_rate as u64,
+            dst_rate: dst_rate.max(1) as u64,
+            pos_num: 0,
+            last_sample: 0.0,
+        }
+    }
+
+    fn process(&mut self, block: &[f32]) -> Vec<f32> {
+        if block.is_empty() {
+            return Vec::new();
+        }
+
+        let virtual_len = block.len() + 1;
+        let mut out = Vec::new();
+        let dst = self.dst_rate;
+        while (self.pos_num / dst) as usize + 1 < virtual_len {
+            let idx = (self.pos_num / dst) as usize;
+            let frac = (self.pos_num % dst) as f32 / dst as f32;
+            let a = if idx == 0 {
+                self.last_sample
+            } else {
+                block[idx - 1]
+            };
+            let b = block[idx];
+            out.push(a * (1.0 - frac) + b * frac);
+            self.pos_num += self.src_rate;
+        }
+
+        self.pos_num -= (virtual_len - 1) as u64 * dst;
+        self.last_sample = *block.last().unwrap();
+        out
+    }
+}
+
+/// Downmixes/upmixes interleaved audio to `dst_channels` and resamples each output channel
+/// independently to `dst_rate`, carrying state across calls so chunked input resamples without
+/// clicks at block/poll boundaries. Shared by the live capture path (`linux.rs`) and the Opus
+/// streaming path (`opus_stream.rs`), which used to each hand-roll their own copy of this.
+pub(crate) struct StreamResampler {
+    src_channels: u16,
+    dst_channels: u16,
+    channel_resamplers: Vec<LinearResampler>,
+}
+
+impl StreamResampler {
+    pub(crate) fn new(src_channels: u16, src_rate: u32, dst_channels: u16, dst_rate: u32) -> Self {
+        let dst_channels = dst_channels.max(1);
+        let channel_resamplers = (0..dst_channels)
+            .map(|_| LinearResampler::new(src_rate, dst_rate))
+            .collect();
+        Self {
+            src_channels: src_channels.max(1),
+            dst_channels,
+            channel_resamplers,
+        }
+    }
+
+    pub(crate) fn process(&mut self, data: &[f32]) -> Vec<f32> {
+        if data.is_empty() {
+            return Vec::new();
+        }
+        let src_channels = self.src_channels as usize;
+        let dst_channels = self.dst_channels as usize;
+        let frames = data.len() / src_channels;
+
+        let mut per_channel: Vec<Vec<f32>> = vec![Vec::with_capacity(frames); dst_channels];
+        for frame_idx in 0..frames {
+            let frame = &data[frame_idx * src_channels..frame_idx * src_channels + src_channels];
+            if dst_channels == src_channels {
+                for (c, sample) in frame.iter().enumerate() {
+                    per_channel[c].push(*sample);
+                }
+            } else {
+                let mono = frame.iter().sum::<f32>() / frame.len() as f32;
+                for channel in per_channel.iter_mut() {
+                    channel.push(mono);
+                }
+            }
+        }
+
+        let resampled_channels: Vec<Vec<f32>> = per_channel
+            .into_iter()
+            .zip(self.channel_resamplers.iter_mut())
+            .map(|(samples, resampler)| resampler.process(&samples))
+            .collect();
+
+        interleave(&resampled_channels)
+    }
+}
+
+fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+    if channels.is_empty() {
+        return Vec::new();
+    }
+    let frames = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+    let mut out = Vec::with_capacity(frames * channels.len());
+    for frame in 0..frames {
+        for channel in channels {
+            out.push(channel[frame]);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(len: usize) -> Vec<f32> {
+        (0..len).map(|i| i as f32 / len as f32).collect()
+    }
+
+    /// Feeding a signal in two blocks through one resampler must give the same output as
+    /// feeding it through in one block, sample for sample. If block boundaries weren't
+    /// handled (no carried-over `last_sample`), the first block split this way would lose
+    /// the interpolation context and produce an audible click where the blocks join.
+    #[test]
+    fn linear_resampler_is_continuous_across_block_boundaries() {
+        let signal = ramp(200);
+
+        let mut whole = LinearResampler::new(48_000, 16_000);
+        let whole_out = whole.process(&signal);
+
+        let (first, second) = signal.split_at(77);
+        let mut chunked = LinearResampler::new(48_000, 16_000);
+        let mut chunked_out = chunked.process(first);
+        chunked_out.extend(chunked.process(second));
+
+        assert_eq!(whole_out.len(), chunked_out.len());
+        for (a, b) in whole_out.iter().zip(chunked_out.iter()) {
+            assert!((a - b).abs() < 1e-6, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn linear_resampler_upsamples_without_clicks_at_the_boundary() {
+        let signal = ramp(100);
+
+        let mut whole = LinearResampler::new(16_000, 48_000);
+        let whole_out = whole.process(&signal);
+
+        let (first, second) = signal.split_at(40);
+        let mut chunked = LinearResampler::new(16_000, 48_000);
+        let mut chunked_out = chunked.process(first);
+        chunked_out.extend(chunked.process(second));
+
+        assert_eq!(whole_out.len(), chunked_out.len());
+        for (a, b) in whole_out.iter().zip(chunked_out.iter()) {
+            assert!((a - b).abs() < 1e-6, "{} vs {}", a, b);
+        }
+    }
+
+    /// `StreamResampler` downmixes stereo to mono before resampling; verify the blend and the
+    /// resample both carry state correctly across a chunk boundary.
+    #[test]
+    fn stream_resampler_downmix_is_continuous_across_chunks() {
+        let frames = 150;
+        let mut stereo = Vec::with_capacity(frames * 2);
+        for i in 0..frames {
+            let t = i as f32 / frames as f32;
+            stereo.push(t);
+            stereo.push(-t);
+        }
+
+        let mut whole = StreamResampler::new(2, 48_000, 1, 16_000);
+        let whole_out = whole.process(&stereo);
+
+        let split_frame = 53;
+        let mut chunked = StreamResampler::new(2, 48_000, 1, 16_000);
+        let mut chunked_out = chunked.process(&stereo[..split_frame * 2]);
+        chunked_out.extend(chunked.process(&stereo[split_frame * 2..]));
+
+        assert_eq!(whole_out.len(), chunked_out.len());
+        for (a, b) in whole_out.iter().zip(chunked_out.iter()) {
+            assert!((a - b).abs() < 1e-6, "{} vs {}", a, b);
+        }
+    }
+}